@@ -0,0 +1,215 @@
+use std::{
+    error::Error,
+    fmt::{self, Display}
+};
+
+/// An error in the grammar, or something that can't be generated from it
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GrammarError {
+    /// The line the error is on, starting at 1, or 0 if it isn't about a
+    /// specific line
+    pub line: usize,
+    /// What went wrong
+    pub message: String
+}
+impl Display for GrammarError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.line == 0 {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "line {}: {}", self.line, self.message)
+        }
+    }
+}
+impl Error for GrammarError {}
+
+/// The right hand side of a grammar rule
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Rule {
+    /// A reference to another rule, like `Expr`
+    Node(String),
+    /// A token with this text, like `'+'`
+    Token(String),
+    /// A rule with a name for its accessor, like `lhs:Expr`
+    Labeled(String, Box<Rule>),
+    /// Rules following each other
+    Seq(Vec<Rule>),
+    /// A choice between rules, like `A | B`
+    Alt(Vec<Rule>),
+    /// An optional rule, like `A?`
+    Opt(Box<Rule>),
+    /// A repeated rule, like `A*`
+    Rep(Box<Rule>)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Ident(String),
+    Literal(String),
+    Punct(char)
+}
+
+/// Split the grammar into tokens, together with their line numbers
+fn tokenize(source: &str) -> Result<Vec<(usize, Token)>, GrammarError> {
+    let mut tokens = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let mut chars = line.chars().peekable();
+        while let Some(c) = chars.next() {
+            match c {
+                '/' if chars.peek() == Some(&'/') => break,
+                c if c.is_whitespace() => (),
+                '=' | '|' | '*' | '?' | '(' | ')' | ':' => tokens.push((line_number, Token::Punct(c))),
+                '\'' => {
+                    let unterminated = || GrammarError {
+                        line: line_number,
+                        message: "unterminated token".into()
+                    };
+                    let mut text = String::new();
+                    loop {
+                        match chars.next() {
+                            Some('\'') => break,
+                            Some('\\') => text.push(chars.next().ok_or_else(unterminated)?),
+                            Some(c) => text.push(c),
+                            None => return Err(unterminated())
+                        }
+                    }
+                    if text.is_empty() {
+                        return Err(GrammarError { line: line_number, message: "empty token".into() });
+                    }
+                    tokens.push((line_number, Token::Literal(text)));
+                },
+                c if c.is_alphabetic() || c == '_' => {
+                    let mut ident = c.to_string();
+                    while let Some(&c) = chars.peek() {
+                        if !c.is_alphanumeric() && c != '_' {
+                            break;
+                        }
+                        ident.push(c);
+                        chars.next();
+                    }
+                    tokens.push((line_number, Token::Ident(ident)));
+                },
+                c => return Err(GrammarError {
+                    line: line_number,
+                    message: format!("unexpected character {:?}", c)
+                })
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize
+}
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, token)| token)
+    }
+    fn error<T>(&self, message: &str) -> Result<T, GrammarError> {
+        let line = self.tokens.get(self.pos)
+            .or_else(|| self.tokens.last())
+            .map(|&(line, _)| line)
+            .unwrap_or(0);
+        Err(GrammarError { line, message: message.into() })
+    }
+    fn expect(&mut self, c: char) -> Result<(), GrammarError> {
+        if self.peek() != Some(&Token::Punct(c)) {
+            return self.error(&format!("expected {:?}", c));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+    /// Return true if the next tokens start a new rule definition
+    fn at_definition(&self) -> bool {
+        matches!(
+            (self.tokens.get(self.pos), self.tokens.get(self.pos + 1)),
+            (Some((_, Token::Ident(_))), Some((_, Token::Punct('='))))
+        )
+    }
+    fn alt(&mut self) -> Result<Rule, GrammarError> {
+        let mut alts = vec![self.seq()?];
+        while self.peek() == Some(&Token::Punct('|')) {
+            self.pos += 1;
+            alts.push(self.seq()?);
+        }
+        Ok(if alts.len() == 1 { alts.pop().unwrap() } else { Rule::Alt(alts) })
+    }
+    fn seq(&mut self) -> Result<Rule, GrammarError> {
+        let mut seq = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Token::Punct(')')) | Some(Token::Punct('|')) => break,
+                Some(_) if self.at_definition() => break,
+                Some(_) => seq.push(self.postfix()?)
+            }
+        }
+        match seq.len() {
+            0 => self.error("expected a rule"),
+            1 => Ok(seq.pop().unwrap()),
+            _ => Ok(Rule::Seq(seq))
+        }
+    }
+    fn postfix(&mut self) -> Result<Rule, GrammarError> {
+        let mut rule = self.atom()?;
+        loop {
+            rule = match self.peek() {
+                Some(Token::Punct('*')) => Rule::Rep(Box::new(rule)),
+                Some(Token::Punct('?')) => Rule::Opt(Box::new(rule)),
+                _ => return Ok(rule)
+            };
+            self.pos += 1;
+        }
+    }
+    fn atom(&mut self) -> Result<Rule, GrammarError> {
+        let token = match self.peek() {
+            Some(token) => token.clone(),
+            None => return self.error("unexpected end of grammar")
+        };
+        self.pos += 1;
+        match token {
+            Token::Ident(name) => if self.peek() == Some(&Token::Punct(':')) {
+                self.pos += 1;
+                Ok(Rule::Labeled(name, Box::new(self.postfix()?)))
+            } else {
+                Ok(Rule::Node(name))
+            },
+            Token::Literal(text) => Ok(Rule::Token(text)),
+            Token::Punct('(') => {
+                let rule = self.alt()?;
+                self.expect(')')?;
+                Ok(rule)
+            },
+            Token::Punct(c) => {
+                self.pos -= 1;
+                self.error(&format!("unexpected {:?}", c))
+            }
+        }
+    }
+}
+
+/// Parse a grammar into its rules, in order
+pub fn parse(source: &str) -> Result<Vec<(String, Rule)>, GrammarError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0
+    };
+    let mut rules = Vec::new();
+    while parser.peek().is_some() {
+        let line = parser.tokens[parser.pos].0;
+        let name = match parser.peek() {
+            Some(Token::Ident(name)) => name.clone(),
+            _ => return parser.error("expected a rule name")
+        };
+        parser.pos += 1;
+        parser.expect('=')?;
+        let rule = parser.alt()?;
+        if rules.iter().any(|(existing, _)| *existing == name) {
+            return Err(GrammarError { line, message: format!("rule {} is defined twice", name) });
+        }
+        rules.push((name, rule));
+    }
+    Ok(rules)
+}
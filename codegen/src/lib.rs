@@ -0,0 +1,573 @@
+//! Generates a kind enum and typed AST wrappers for rowan2 trees from a
+//! grammar, in a format similar to ungrammar:
+//!
+//! ```text
+//! // Comments start with two slashes
+//! Root = Expr*
+//! Expr = Literal | Binary | Paren
+//! Literal = 'number'
+//! Binary = lhs:Expr op:('+' | '-') rhs:Expr
+//! Paren = '(' Expr ')'
+//! ```
+//!
+//! Rules that are only a choice between other rules become enums, all other
+//! rules become node kinds with a wrapper struct. Tokens become kinds without
+//! wrappers. Accessors are named after their labels, or after the rule or
+//! token they return. The kind enum implements `rowan2::SyntaxKind`.
+//!
+//! Accessors find their node by skipping the children that earlier fields
+//! could return, so `value` in `Let = name:Name '=' value:Expr` skips the
+//! name, since a name is an expression too. Grammars where that count isn't
+//! known, like `Foo = Expr* last:Expr`, are rejected.
+//!
+//! To use it from `build.rs`:
+//!
+//! ```no_run
+//! extern crate rowan2_codegen;
+//!
+//! fn main() {
+//!     let out = std::env::var("OUT_DIR").unwrap();
+//!     rowan2_codegen::generate_file("grammar.ungram", format!("{}/ast.rs", out)).unwrap();
+//! }
+//! ```
+//!
+//! And then `include!(concat!(env!("OUT_DIR"), "/ast.rs"));` in the crate.
+
+mod grammar;
+
+pub use grammar::{GrammarError, Rule};
+
+use std::{
+    error::Error,
+    fmt::Write,
+    fs,
+    path::Path
+};
+
+const KEYWORDS: &[&str] = &[
+    "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn", "for",
+    "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return",
+    "self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "async", "await", "dyn", "abstract", "become", "box", "do", "final", "macro",
+    "override", "priv", "typeof", "unsized", "virtual", "yield", "try"
+];
+
+const PUNCTUATION: &[(char, &str)] = &[
+    ('+', "Plus"), ('-', "Minus"), ('*', "Star"), ('/', "Slash"), ('%', "Percent"),
+    ('^', "Caret"), ('!', "Bang"), ('&', "Amp"), ('|', "Pipe"), ('=', "Eq"), ('<', "Lt"),
+    ('>', "Gt"), ('@', "At"), ('.', "Dot"), (',', "Comma"), (';', "Semi"), (':', "Colon"),
+    ('#', "Pound"), ('$', "Dollar"), ('?', "Question"), ('~', "Tilde"), ('\'', "Quote"),
+    ('"', "DoubleQuote"), ('\\', "Backslash"), ('(', "LParen"), (')', "RParen"),
+    ('[', "LBrack"), (']', "RBrack"), ('{', "LBrace"), ('}', "RBrace")
+];
+
+fn error<T>(message: String) -> Result<T, GrammarError> {
+    Err(GrammarError { line: 0, message })
+}
+
+/// Turn `snake_case` into `CamelCase`
+fn camel_case(name: &str) -> String {
+    let mut camel = String::new();
+    let mut upper = true;
+    for c in name.chars() {
+        if c == '_' {
+            upper = true;
+        } else if upper {
+            camel.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            camel.push(c);
+        }
+    }
+    camel
+}
+/// Turn `CamelCase` into `snake_case`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i > 0 {
+                snake.push('_');
+            }
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+    snake
+}
+/// Make the name usable as a method name
+fn method_name(name: String) -> String {
+    if KEYWORDS.contains(&&*name) {
+        name + "_"
+    } else {
+        name
+    }
+}
+
+/// Get the name of the kind of a token. Words are turned into CamelCase and
+/// get a `Token` suffix, punctuation is spelled out.
+fn token_kind(text: &str) -> Result<String, GrammarError> {
+    if text.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        return Ok(camel_case(text) + "Token");
+    }
+    let mut name = String::new();
+    for c in text.chars() {
+        match PUNCTUATION.iter().find(|&&(punct, _)| punct == c) {
+            Some((_, punct)) => name.push_str(punct),
+            None if c.is_alphanumeric() => name.extend(c.to_uppercase()),
+            None => return error(format!("can't name token '{}'", text))
+        }
+    }
+    Ok(name)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum FieldKind {
+    /// A child node of the rule with this name
+    Node(String),
+    /// A token with any of these kinds
+    Tokens(Vec<String>)
+}
+#[derive(Clone, Debug)]
+struct Field {
+    name: String,
+    kind: FieldKind,
+    many: bool,
+    /// whether the field may be missing, because it's optional or in one of
+    /// multiple choices
+    optional: bool
+}
+
+/// Find all accessors of a rule
+fn collect_fields(rule: &Rule, many: bool, optional: bool, label: Option<&str>, fields: &mut Vec<Field>) -> Result<(), GrammarError> {
+    let field = match *rule {
+        Rule::Node(ref name) => Field {
+            name: match label {
+                Some(label) => label.to_string(),
+                None if many => snake_case(name) + "s",
+                None => snake_case(name)
+            },
+            kind: FieldKind::Node(name.clone()),
+            many,
+            optional
+        },
+        Rule::Token(ref text) => {
+            let kind = token_kind(text)?;
+            let base = if kind.ends_with("Token") { &kind[..kind.len() - 5] } else { &kind };
+            Field {
+                name: match label {
+                    Some(label) => label.to_string(),
+                    None if many => snake_case(base) + "_tokens",
+                    None => snake_case(base) + "_token"
+                },
+                kind: FieldKind::Tokens(vec![kind]),
+                many,
+                optional
+            }
+        },
+        Rule::Labeled(ref label, ref rule) => return collect_fields(rule, many, optional, Some(label), fields),
+        Rule::Opt(ref rule) => return collect_fields(rule, many, true, label, fields),
+        Rule::Rep(ref rule) => return collect_fields(rule, true, true, label, fields),
+        Rule::Alt(ref alts) if label.is_some() => {
+            let mut kinds = Vec::new();
+            for alt in alts {
+                match *alt {
+                    Rule::Token(ref text) => kinds.push(token_kind(text)?),
+                    _ => return error(format!("label {} must be on a rule, a token or a choice of tokens", label.unwrap()))
+                }
+            }
+            Field {
+                name: label.unwrap().to_string(),
+                kind: FieldKind::Tokens(kinds),
+                many,
+                optional
+            }
+        },
+        Rule::Seq(ref rules) | Rule::Alt(ref rules) => {
+            if let Some(label) = label {
+                return error(format!("label {} must be on a rule, a token or a choice of tokens", label));
+            }
+            let optional = optional || matches!(*rule, Rule::Alt(_));
+            for rule in rules {
+                collect_fields(rule, many, optional, None, fields)?;
+            }
+            return Ok(());
+        }
+    };
+    let field = Field { name: method_name(field.name), ..field };
+
+    // Using the same label twice, like in `args:Expr (',' args:Expr)*`, gives
+    // one accessor for all of them
+    match fields.iter_mut().find(|existing| existing.name == field.name) {
+        Some(ref mut existing) if existing.kind == field.kind => {
+            existing.many = true;
+            existing.optional &= field.optional;
+            Ok(())
+        },
+        Some(_) => error(format!("there are two different accessors named {}, add labels to tell them apart", field.name)),
+        None => {
+            fields.push(field);
+            Ok(())
+        }
+    }
+}
+
+/// Get the rule names of all variants, if this rule is a choice between
+/// other rules
+fn enum_variants(rule: &Rule) -> Option<Vec<&str>> {
+    match *rule {
+        Rule::Alt(ref alts) => alts.iter()
+            .map(|alt| match *alt {
+                Rule::Node(ref name) => Some(&**name),
+                _ => None
+            })
+            .collect(),
+        _ => None
+    }
+}
+
+/// Add the kinds of all nodes that can be cast to the rule. That's just the
+/// rule itself, unless it's a choice between other rules.
+fn node_kinds<'a>(name: &'a str, rules: &'a [(String, Rule)], visited: &mut Vec<&'a str>, kinds: &mut Vec<&'a str>) {
+    if visited.contains(&name) {
+        return;
+    }
+    visited.push(name);
+    let (name, rule) = rules.iter().find(|(rule, _)| rule == name).unwrap();
+    match enum_variants(rule) {
+        Some(variants) => for variant in variants {
+            node_kinds(variant, rules, visited, kinds);
+        },
+        None => if !kinds.contains(&&**name) {
+            kinds.push(name);
+        }
+    }
+}
+/// Get the kinds of all nodes or tokens a field can return
+fn field_kinds<'a>(field: &'a Field, rules: &'a [(String, Rule)]) -> Vec<&'a str> {
+    match field.kind {
+        FieldKind::Node(ref node) => {
+            let mut kinds = Vec::new();
+            node_kinds(node, rules, &mut Vec::new(), &mut kinds);
+            kinds
+        },
+        FieldKind::Tokens(ref kinds) => kinds.iter().map(|kind| &**kind).collect()
+    }
+}
+
+/// Write the rule like it was written in the grammar. `precedence` is 0 for
+/// choices, 1 for sequences and 2 for everything else.
+fn write_rule(out: &mut String, rule: &Rule, precedence: u8) {
+    let (own, parenthesize) = match *rule {
+        Rule::Alt(_) => (0, precedence > 0),
+        Rule::Seq(_) => (1, precedence > 1),
+        _ => (2, false)
+    };
+    if parenthesize {
+        out.push('(');
+    }
+    match *rule {
+        Rule::Node(ref name) => out.push_str(name),
+        Rule::Token(ref text) => {
+            out.push('\'');
+            out.push_str(&text.replace('\\', "\\\\").replace('\'', "\\'"));
+            out.push('\'');
+        },
+        Rule::Labeled(ref label, ref rule) => {
+            out.push_str(label);
+            out.push(':');
+            write_rule(out, rule, 2);
+        },
+        Rule::Seq(ref rules) | Rule::Alt(ref rules) => for (i, rule) in rules.iter().enumerate() {
+            if i > 0 {
+                out.push_str(if own == 0 { " | " } else { " " });
+            }
+            write_rule(out, rule, own + 1);
+        },
+        Rule::Opt(ref rule) => {
+            write_rule(out, rule, 2);
+            out.push('?');
+        },
+        Rule::Rep(ref rule) => {
+            write_rule(out, rule, 2);
+            out.push('*');
+        }
+    }
+    if parenthesize {
+        out.push(')');
+    }
+}
+
+/// Find all tokens in the rule, in order
+fn collect_tokens<'a>(rule: &'a Rule, tokens: &mut Vec<&'a str>) {
+    match *rule {
+        Rule::Node(_) => (),
+        Rule::Token(ref text) => if !tokens.contains(&&**text) {
+            tokens.push(text);
+        },
+        Rule::Labeled(_, ref rule) | Rule::Opt(ref rule) | Rule::Rep(ref rule) => collect_tokens(rule, tokens),
+        Rule::Seq(ref rules) | Rule::Alt(ref rules) => for rule in rules {
+            collect_tokens(rule, tokens);
+        }
+    }
+}
+/// Check that all rules the rule refers to exist
+fn check_references(rule: &Rule, rules: &[(String, Rule)], name: &str) -> Result<(), GrammarError> {
+    match *rule {
+        Rule::Node(ref target) => if !rules.iter().any(|(rule, _)| rule == target) {
+            return error(format!("rule {} refers to {}, which doesn't exist", name, target));
+        },
+        Rule::Token(_) => (),
+        Rule::Labeled(_, ref rule) | Rule::Opt(ref rule) | Rule::Rep(ref rule) => check_references(rule, rules, name)?,
+        Rule::Seq(ref alts) | Rule::Alt(ref alts) => for rule in alts {
+            check_references(rule, rules, name)?;
+        }
+    }
+    Ok(())
+}
+
+const ROOT: &str = "R: ::rowan2::TreeRoot<SyntaxKind>";
+const NODE: &str = "::rowan2::Node<SyntaxKind, R>";
+
+fn generate_kinds(out: &mut String, tokens: &[(String, &str)], nodes: &[&str]) {
+    let kinds: Vec<&str> = tokens.iter().map(|(kind, _)| &**kind).chain(nodes.iter().cloned()).collect();
+
+    out.push_str("/// All kinds of tokens and nodes in the grammar\n");
+    out.push_str("#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]\n");
+    out.push_str("#[repr(u16)]\n");
+    out.push_str("pub enum SyntaxKind {\n");
+    for (kind, text) in tokens {
+        writeln!(out, "    /// The token `{}`", text).unwrap();
+        writeln!(out, "    {},", kind).unwrap();
+    }
+    for node in nodes {
+        writeln!(out, "    /// The node `{}`", node).unwrap();
+        writeln!(out, "    {},", node).unwrap();
+    }
+    out.push_str("}\n");
+
+    out.push_str("impl SyntaxKind {\n");
+    out.push_str("    /// Get the name of this kind\n");
+    out.push_str("    pub fn name(self) -> &'static str {\n");
+    out.push_str("        match self {\n");
+    for kind in &kinds {
+        writeln!(out, "            SyntaxKind::{} => \"{}\",", kind, kind).unwrap();
+    }
+    out.push_str("        }\n    }\n");
+    out.push_str("    /// Look up a kind by its name\n");
+    out.push_str("    pub fn from_name(name: &str) -> Option<Self> {\n");
+    out.push_str("        match name {\n");
+    for kind in &kinds {
+        writeln!(out, "            \"{}\" => Some(SyntaxKind::{}),", kind, kind).unwrap();
+    }
+    out.push_str("            _ => None\n        }\n    }\n}\n");
+
+    out.push_str("impl ::rowan2::SyntaxKind for SyntaxKind {\n");
+    out.push_str("    fn name(self) -> &'static str {\n        SyntaxKind::name(self)\n    }\n");
+    out.push_str("    fn from_name(name: &str) -> Option<Self> {\n        SyntaxKind::from_name(name)\n    }\n");
+    out.push_str("    fn is_token(self) -> bool {\n");
+    if tokens.is_empty() {
+        out.push_str("        false\n");
+    } else {
+        let pattern = tokens.iter()
+            .map(|(kind, _)| format!("SyntaxKind::{}", kind))
+            .collect::<Vec<_>>()
+            .join(" | ");
+        writeln!(out, "        matches!(self, {})", pattern).unwrap();
+    }
+    out.push_str("    }\n");
+    out.push_str("    fn into_raw(self) -> u16 {\n        self as u16\n    }\n");
+    out.push_str("    fn from_raw(raw: u16) -> Option<Self> {\n");
+    out.push_str("        match raw {\n");
+    for (i, kind) in kinds.iter().enumerate() {
+        writeln!(out, "            {} => Some(SyntaxKind::{}),", i, kind).unwrap();
+    }
+    out.push_str("            _ => None\n        }\n    }\n}\n");
+}
+
+fn generate_node(out: &mut String, name: &str, rule: &Rule, rules: &[(String, Rule)]) -> Result<(), GrammarError> {
+    let mut fields = Vec::new();
+    collect_fields(rule, false, false, None, &mut fields)?;
+
+    // Each accessor goes through all children its field could be, so it has
+    // to skip those of earlier fields. For example in `name:Literal '='
+    // value:Expr`, `value` skips one expression because `name` is one too.
+    // That's only possible if those earlier fields are always there exactly
+    // once, and can't be anything this field can't be.
+    let kinds: Vec<_> = fields.iter().map(|field| field_kinds(field, rules)).collect();
+    let mut skip = Vec::new();
+    for (i, field) in fields.iter().enumerate() {
+        let mut count = 0;
+        for (j, other) in fields[..i].iter().enumerate() {
+            if !kinds[j].iter().any(|kind| kinds[i].contains(kind)) {
+                continue;
+            }
+            if other.many || other.optional || !kinds[j].iter().all(|kind| kinds[i].contains(kind)) {
+                return error(format!(
+                    "can't tell {} apart from {} in rule {}, because they can be the same kind of node",
+                    field.name, other.name, name
+                ));
+            }
+            count += 1;
+        }
+        skip.push(count);
+    }
+
+    let mut doc = String::new();
+    write_rule(&mut doc, rule, 0);
+    writeln!(out, "\n/// `{} = {}`", name, doc).unwrap();
+    out.push_str("#[derive(Clone)]\n");
+    writeln!(out, "pub struct {}<{}>({});", name, ROOT, NODE).unwrap();
+
+    writeln!(out, "impl<{}> ::rowan2::AstNode<SyntaxKind, R> for {}<R> {{", ROOT, name).unwrap();
+    out.push_str("    fn can_cast(kind: SyntaxKind) -> bool {\n");
+    writeln!(out, "        kind == SyntaxKind::{}", name).unwrap();
+    out.push_str("    }\n");
+    writeln!(out, "    fn cast(node: {}) -> Option<Self> {{", NODE).unwrap();
+    writeln!(out, "        if Self::can_cast(node.kind()) {{ Some({}(node)) }} else {{ None }}", name).unwrap();
+    out.push_str("    }\n");
+    writeln!(out, "    fn syntax(&self) -> &{} {{", NODE).unwrap();
+    out.push_str("        &self.0\n    }\n}\n");
+
+    writeln!(out, "impl<{}> ::std::fmt::Debug for {}<R> {{", ROOT, name).unwrap();
+    out.push_str("    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {\n");
+    out.push_str("        ::std::fmt::Debug::fmt(&self.0, f)\n    }\n}\n");
+
+    if fields.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "impl<{}> {}<R> {{", ROOT, name).unwrap();
+    for (field, &skip) in fields.iter().zip(&skip) {
+        match field.kind {
+            FieldKind::Node(ref node) => if field.many {
+                writeln!(out, "    pub fn {}(&self) -> impl Iterator<Item = {}<R>> {{", field.name, node).unwrap();
+                if skip == 0 {
+                    out.push_str("        self.0.children_of()\n    }\n");
+                } else {
+                    writeln!(out, "        self.0.children_of().skip({})\n    }}", skip).unwrap();
+                }
+            } else {
+                writeln!(out, "    pub fn {}(&self) -> Option<{}<R>> {{", field.name, node).unwrap();
+                writeln!(out, "        self.0.children_of().nth({})\n    }}", skip).unwrap();
+            },
+            FieldKind::Tokens(ref kinds) => {
+                let pattern = kinds.iter()
+                    .map(|kind| format!("SyntaxKind::{}", kind))
+                    .collect::<Vec<_>>()
+                    .join(" | ");
+                if field.many {
+                    writeln!(out, "    pub fn {}(&self) -> impl Iterator<Item = {}> {{", field.name, NODE).unwrap();
+                } else {
+                    writeln!(out, "    pub fn {}(&self) -> Option<{}> {{", field.name, NODE).unwrap();
+                }
+                writeln!(out, "        self.0.children().filter(|node| matches!(node.kind(), {}))", pattern).unwrap();
+                if !field.many {
+                    writeln!(out, "            .nth({})", skip).unwrap();
+                } else if skip > 0 {
+                    writeln!(out, "            .skip({})", skip).unwrap();
+                }
+                out.push_str("    }\n");
+            }
+        }
+    }
+    out.push_str("}\n");
+    Ok(())
+}
+
+fn generate_enum(out: &mut String, name: &str, rule: &Rule, variants: &[&str]) {
+    let mut doc = String::new();
+    write_rule(&mut doc, rule, 0);
+    writeln!(out, "\n/// `{} = {}`", name, doc).unwrap();
+    out.push_str("#[derive(Clone)]\n");
+    writeln!(out, "pub enum {}<{}> {{", name, ROOT).unwrap();
+    for variant in variants {
+        writeln!(out, "    {}({}<R>),", variant, variant).unwrap();
+    }
+    out.push_str("}\n");
+
+    writeln!(out, "impl<{}> ::rowan2::AstNode<SyntaxKind, R> for {}<R> {{", ROOT, name).unwrap();
+    out.push_str("    fn can_cast(kind: SyntaxKind) -> bool {\n");
+    let can_cast = variants.iter()
+        .map(|variant| format!("<{}<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)", variant))
+        .collect::<Vec<_>>()
+        .join("\n            || ");
+    writeln!(out, "        {}", can_cast).unwrap();
+    out.push_str("    }\n");
+    writeln!(out, "    fn cast(node: {}) -> Option<Self> {{", NODE).unwrap();
+    out.push_str("        let kind = node.kind();\n");
+    for variant in variants {
+        writeln!(out, "        if <{}<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {{", variant).unwrap();
+        writeln!(out, "            return ::rowan2::AstNode::cast(node).map({}::{});", name, variant).unwrap();
+        out.push_str("        }\n");
+    }
+    out.push_str("        None\n    }\n");
+    writeln!(out, "    fn syntax(&self) -> &{} {{", NODE).unwrap();
+    out.push_str("        match *self {\n");
+    for variant in variants {
+        writeln!(out, "            {}::{}(ref node) => ::rowan2::AstNode::syntax(node),", name, variant).unwrap();
+    }
+    out.push_str("        }\n    }\n}\n");
+
+    writeln!(out, "impl<{}> ::std::fmt::Debug for {}<R> {{", ROOT, name).unwrap();
+    out.push_str("    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {\n");
+    out.push_str("        ::std::fmt::Debug::fmt(::rowan2::AstNode::syntax(self), f)\n    }\n}\n");
+
+    for variant in variants {
+        writeln!(out, "impl<{}> From<{}<R>> for {}<R> {{", ROOT, variant, name).unwrap();
+        writeln!(out, "    fn from(node: {}<R>) -> Self {{", variant).unwrap();
+        writeln!(out, "        {}::{}(node)\n    }}\n}}", name, variant).unwrap();
+    }
+}
+
+/// Generate Rust code from a grammar. The code contains a `SyntaxKind` enum
+/// and wrapper types for all rules, which refer to the `rowan2` crate.
+pub fn generate(grammar: &str) -> Result<String, GrammarError> {
+    let rules = grammar::parse(grammar)?;
+    if rules.is_empty() {
+        return error("the grammar is empty".into());
+    }
+
+    let mut tokens = Vec::new();
+    for (name, rule) in &rules {
+        check_references(rule, &rules, name)?;
+        collect_tokens(rule, &mut tokens);
+    }
+    let mut token_kinds: Vec<(String, &str)> = Vec::new();
+    for text in tokens {
+        let kind = token_kind(text)?;
+        if token_kinds.iter().any(|(existing, _)| *existing == kind) {
+            return error(format!("two tokens are both named {}", kind));
+        }
+        token_kinds.push((kind, text));
+    }
+    let nodes: Vec<&str> = rules.iter()
+        .filter(|(_, rule)| enum_variants(rule).is_none())
+        .map(|(name, _)| &**name)
+        .collect();
+    if let Some(node) = nodes.iter().find(|node| token_kinds.iter().any(|(kind, _)| kind == *node)) {
+        return error(format!("rule {} has the same name as a token", node));
+    }
+
+    let mut out = String::new();
+    out.push_str("// Generated by rowan2-codegen, don't edit by hand\n\n");
+    generate_kinds(&mut out, &token_kinds, &nodes);
+    for (name, rule) in &rules {
+        match enum_variants(rule) {
+            Some(variants) => generate_enum(&mut out, name, rule, &variants),
+            None => generate_node(&mut out, name, rule, &rules)?
+        }
+    }
+    Ok(out)
+}
+
+/// Read a grammar from one file and write the generated code to another.
+/// Meant to be called from `build.rs`, so it also tells cargo to run the
+/// build script again when the grammar changes.
+pub fn generate_file<P: AsRef<Path>, Q: AsRef<Path>>(grammar: P, output: Q) -> Result<(), Box<dyn Error>> {
+    let grammar = grammar.as_ref();
+    println!("cargo:rerun-if-changed={}", grammar.display());
+    let code = generate(&fs::read_to_string(grammar)?)?;
+    fs::write(output, code)?;
+    Ok(())
+}
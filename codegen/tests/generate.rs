@@ -0,0 +1,134 @@
+#[macro_use]
+extern crate rowan2;
+extern crate rowan2_codegen;
+
+use rowan2::{AstNode, Node, OwnedRoot};
+use std::{env, fs};
+
+#[allow(dead_code)]
+mod ast {
+    include!("golden/ast.rs");
+}
+
+use ast::{Call, Expr, Item, Let, Root, SyntaxKind};
+
+const GRAMMAR: &str = include_str!("golden/grammar.ungram");
+
+#[test]
+fn golden() {
+    let generated = rowan2_codegen::generate(GRAMMAR).unwrap();
+    // Run with UPDATE_GOLDEN=1 to accept changes to the output
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(concat!(env!("CARGO_MANIFEST_DIR"), "/tests/golden/ast.rs"), &generated).unwrap();
+    }
+    assert_eq!(generated, include_str!("golden/ast.rs"));
+}
+
+/// `let x = 1; let y = f(x, 2 + 3);`
+fn tree() -> Root<OwnedRoot<SyntaxKind>> {
+    let root: Node<SyntaxKind, OwnedRoot<SyntaxKind>> = tree! { ast::SyntaxKind:
+        Root {
+            Let {
+                LetToken "let",
+                Name { IdentToken "x" },
+                Eq "=",
+                Literal { NumberToken "1" },
+                Semi ";"
+            },
+            Let {
+                LetToken "let",
+                Name { IdentToken "y" },
+                Eq "=",
+                Call {
+                    Name { IdentToken "f" },
+                    LParen "(",
+                    Name { IdentToken "x" },
+                    Comma ",",
+                    Binary {
+                        Literal { NumberToken "2" },
+                        Plus "+",
+                        Literal { NumberToken "3" }
+                    },
+                    RParen ")"
+                },
+                Semi ";"
+            }
+        }
+    };
+    Root::cast(root).unwrap()
+}
+
+fn text<N: AstNode<SyntaxKind, OwnedRoot<SyntaxKind>>>(node: Option<N>) -> String {
+    node.unwrap().syntax().to_string()
+}
+
+#[test]
+fn accessors() {
+    let root = tree();
+    let lets: Vec<Let<_>> = root.items()
+        .map(|item| match item {
+            Item::Let(node) => node,
+            item => panic!("expected let, found {:?}", item)
+        })
+        .collect();
+    assert_eq!(lets.len(), 2);
+
+    // `value` skips the name, even though a name is an expression too
+    assert_eq!(text(lets[0].name()), "x");
+    assert_eq!(text(lets[0].value()), "1");
+    assert!(matches!(lets[0].value(), Some(Expr::Literal(_))));
+    assert_eq!(lets[0].eq_token().unwrap().to_string(), "=");
+
+    let call: Call<_> = match lets[1].value() {
+        Some(Expr::Call(call)) => call,
+        value => panic!("expected call, found {:?}", value)
+    };
+    assert_eq!(text(lets[1].name()), "y");
+    // `args` skips the callee
+    assert_eq!(text(call.callee()), "f");
+    let args: Vec<_> = call.args().map(|arg| arg.syntax().to_string()).collect();
+    assert_eq!(args, ["x", "2+3"]);
+    assert_eq!(call.comma_tokens().count(), 1);
+
+    let binary = match call.args().nth(1) {
+        Some(Expr::Binary(binary)) => binary,
+        arg => panic!("expected binary, found {:?}", arg)
+    };
+    assert_eq!(text(binary.lhs()), "2");
+    assert_eq!(binary.op().unwrap().kind(), SyntaxKind::Plus);
+    assert_eq!(text(binary.rhs()), "3");
+}
+
+#[test]
+fn ambiguous_fields() {
+    let error = |grammar: &str| rowan2_codegen::generate(grammar).unwrap_err().message;
+    let grammar = "Expr = Literal | Name\nLiteral = 'number'\nName = 'ident'\n";
+
+    // The number of repeated or optional nodes isn't known
+    let message = error(&format!("{}Foo = Expr* last:Expr", grammar));
+    assert!(message.contains("can't tell last apart from expr"), "{}", message);
+    let message = error(&format!("{}Range = start:Expr? '..' end:Expr?", grammar));
+    assert!(message.contains("can't tell end apart from start"), "{}", message);
+    let message = error(&format!("{}Foo = ('a' a:Literal | 'b' b:Name) c:Expr", grammar));
+    assert!(message.contains("can't tell c apart from a"), "{}", message);
+    // An expression before a name might not be a name
+    let message = error(&format!("{}Foo = value:Expr name:Name", grammar));
+    assert!(message.contains("can't tell name apart from value"), "{}", message);
+
+    // Fields that can't be the same node are fine
+    assert!(rowan2_codegen::generate(&format!("{}Foo = Literal* name:Name", grammar)).is_ok());
+    assert!(rowan2_codegen::generate(&format!("{}Foo = name:Name Expr*", grammar)).is_ok());
+}
+
+#[test]
+fn unterminated_tokens() {
+    for grammar in &["Foo = 'a", "Foo = 'a\\", "Foo = 'a\\'", "Foo = 'a'\nBar = 'b\\"] {
+        let error = rowan2_codegen::generate(grammar).unwrap_err();
+        assert_eq!(error.message, "unterminated token", "{:?}", grammar);
+        assert_eq!(error.line, grammar.lines().count(), "{:?}", grammar);
+    }
+    // Escaped quotes and backslashes are still fine
+    let generated = rowan2_codegen::generate("Foo = 'a\\'' 'b\\\\'").unwrap();
+    assert!(generated.contains("/// The token `a'`"), "{}", generated);
+    assert!(generated.contains("/// The token `b\\`"), "{}", generated);
+}
@@ -0,0 +1,477 @@
+// Generated by rowan2-codegen, don't edit by hand
+
+/// All kinds of tokens and nodes in the grammar
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u16)]
+pub enum SyntaxKind {
+    /// The token `number`
+    NumberToken,
+    /// The token `ident`
+    IdentToken,
+    /// The token `+`
+    Plus,
+    /// The token `-`
+    Minus,
+    /// The token `(`
+    LParen,
+    /// The token `)`
+    RParen,
+    /// The token `,`
+    Comma,
+    /// The token `let`
+    LetToken,
+    /// The token `=`
+    Eq,
+    /// The token `;`
+    Semi,
+    /// The node `Root`
+    Root,
+    /// The node `Literal`
+    Literal,
+    /// The node `Name`
+    Name,
+    /// The node `Binary`
+    Binary,
+    /// The node `Paren`
+    Paren,
+    /// The node `Call`
+    Call,
+    /// The node `Let`
+    Let,
+}
+impl SyntaxKind {
+    /// Get the name of this kind
+    pub fn name(self) -> &'static str {
+        match self {
+            SyntaxKind::NumberToken => "NumberToken",
+            SyntaxKind::IdentToken => "IdentToken",
+            SyntaxKind::Plus => "Plus",
+            SyntaxKind::Minus => "Minus",
+            SyntaxKind::LParen => "LParen",
+            SyntaxKind::RParen => "RParen",
+            SyntaxKind::Comma => "Comma",
+            SyntaxKind::LetToken => "LetToken",
+            SyntaxKind::Eq => "Eq",
+            SyntaxKind::Semi => "Semi",
+            SyntaxKind::Root => "Root",
+            SyntaxKind::Literal => "Literal",
+            SyntaxKind::Name => "Name",
+            SyntaxKind::Binary => "Binary",
+            SyntaxKind::Paren => "Paren",
+            SyntaxKind::Call => "Call",
+            SyntaxKind::Let => "Let",
+        }
+    }
+    /// Look up a kind by its name
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "NumberToken" => Some(SyntaxKind::NumberToken),
+            "IdentToken" => Some(SyntaxKind::IdentToken),
+            "Plus" => Some(SyntaxKind::Plus),
+            "Minus" => Some(SyntaxKind::Minus),
+            "LParen" => Some(SyntaxKind::LParen),
+            "RParen" => Some(SyntaxKind::RParen),
+            "Comma" => Some(SyntaxKind::Comma),
+            "LetToken" => Some(SyntaxKind::LetToken),
+            "Eq" => Some(SyntaxKind::Eq),
+            "Semi" => Some(SyntaxKind::Semi),
+            "Root" => Some(SyntaxKind::Root),
+            "Literal" => Some(SyntaxKind::Literal),
+            "Name" => Some(SyntaxKind::Name),
+            "Binary" => Some(SyntaxKind::Binary),
+            "Paren" => Some(SyntaxKind::Paren),
+            "Call" => Some(SyntaxKind::Call),
+            "Let" => Some(SyntaxKind::Let),
+            _ => None
+        }
+    }
+}
+impl ::rowan2::SyntaxKind for SyntaxKind {
+    fn name(self) -> &'static str {
+        SyntaxKind::name(self)
+    }
+    fn from_name(name: &str) -> Option<Self> {
+        SyntaxKind::from_name(name)
+    }
+    fn is_token(self) -> bool {
+        matches!(self, SyntaxKind::NumberToken | SyntaxKind::IdentToken | SyntaxKind::Plus | SyntaxKind::Minus | SyntaxKind::LParen | SyntaxKind::RParen | SyntaxKind::Comma | SyntaxKind::LetToken | SyntaxKind::Eq | SyntaxKind::Semi)
+    }
+    fn into_raw(self) -> u16 {
+        self as u16
+    }
+    fn from_raw(raw: u16) -> Option<Self> {
+        match raw {
+            0 => Some(SyntaxKind::NumberToken),
+            1 => Some(SyntaxKind::IdentToken),
+            2 => Some(SyntaxKind::Plus),
+            3 => Some(SyntaxKind::Minus),
+            4 => Some(SyntaxKind::LParen),
+            5 => Some(SyntaxKind::RParen),
+            6 => Some(SyntaxKind::Comma),
+            7 => Some(SyntaxKind::LetToken),
+            8 => Some(SyntaxKind::Eq),
+            9 => Some(SyntaxKind::Semi),
+            10 => Some(SyntaxKind::Root),
+            11 => Some(SyntaxKind::Literal),
+            12 => Some(SyntaxKind::Name),
+            13 => Some(SyntaxKind::Binary),
+            14 => Some(SyntaxKind::Paren),
+            15 => Some(SyntaxKind::Call),
+            16 => Some(SyntaxKind::Let),
+            _ => None
+        }
+    }
+}
+
+/// `Root = items:Item*`
+#[derive(Clone)]
+pub struct Root<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Root<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Root
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Root(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Root<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Root<R> {
+    pub fn items(&self) -> impl Iterator<Item = Item<R>> {
+        self.0.children_of()
+    }
+}
+
+/// `Item = Let | Call | Expr`
+#[derive(Clone)]
+pub enum Item<R: ::rowan2::TreeRoot<SyntaxKind>> {
+    Let(Let<R>),
+    Call(Call<R>),
+    Expr(Expr<R>),
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Item<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        <Let<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+            || <Call<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+            || <Expr<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        let kind = node.kind();
+        if <Let<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Item::Let);
+        }
+        if <Call<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Item::Call);
+        }
+        if <Expr<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Item::Expr);
+        }
+        None
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        match *self {
+            Item::Let(ref node) => ::rowan2::AstNode::syntax(node),
+            Item::Call(ref node) => ::rowan2::AstNode::syntax(node),
+            Item::Expr(ref node) => ::rowan2::AstNode::syntax(node),
+        }
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Item<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(::rowan2::AstNode::syntax(self), f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Let<R>> for Item<R> {
+    fn from(node: Let<R>) -> Self {
+        Item::Let(node)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Call<R>> for Item<R> {
+    fn from(node: Call<R>) -> Self {
+        Item::Call(node)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Expr<R>> for Item<R> {
+    fn from(node: Expr<R>) -> Self {
+        Item::Expr(node)
+    }
+}
+
+/// `Expr = Literal | Name | Binary | Paren | Call`
+#[derive(Clone)]
+pub enum Expr<R: ::rowan2::TreeRoot<SyntaxKind>> {
+    Literal(Literal<R>),
+    Name(Name<R>),
+    Binary(Binary<R>),
+    Paren(Paren<R>),
+    Call(Call<R>),
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Expr<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        <Literal<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+            || <Name<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+            || <Binary<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+            || <Paren<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+            || <Call<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind)
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        let kind = node.kind();
+        if <Literal<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Expr::Literal);
+        }
+        if <Name<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Expr::Name);
+        }
+        if <Binary<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Expr::Binary);
+        }
+        if <Paren<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Expr::Paren);
+        }
+        if <Call<R> as ::rowan2::AstNode<SyntaxKind, R>>::can_cast(kind) {
+            return ::rowan2::AstNode::cast(node).map(Expr::Call);
+        }
+        None
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        match *self {
+            Expr::Literal(ref node) => ::rowan2::AstNode::syntax(node),
+            Expr::Name(ref node) => ::rowan2::AstNode::syntax(node),
+            Expr::Binary(ref node) => ::rowan2::AstNode::syntax(node),
+            Expr::Paren(ref node) => ::rowan2::AstNode::syntax(node),
+            Expr::Call(ref node) => ::rowan2::AstNode::syntax(node),
+        }
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Expr<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(::rowan2::AstNode::syntax(self), f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Literal<R>> for Expr<R> {
+    fn from(node: Literal<R>) -> Self {
+        Expr::Literal(node)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Name<R>> for Expr<R> {
+    fn from(node: Name<R>) -> Self {
+        Expr::Name(node)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Binary<R>> for Expr<R> {
+    fn from(node: Binary<R>) -> Self {
+        Expr::Binary(node)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Paren<R>> for Expr<R> {
+    fn from(node: Paren<R>) -> Self {
+        Expr::Paren(node)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> From<Call<R>> for Expr<R> {
+    fn from(node: Call<R>) -> Self {
+        Expr::Call(node)
+    }
+}
+
+/// `Literal = 'number'`
+#[derive(Clone)]
+pub struct Literal<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Literal<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Literal
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Literal(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Literal<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Literal<R> {
+    pub fn number_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::NumberToken))
+            .nth(0)
+    }
+}
+
+/// `Name = 'ident'`
+#[derive(Clone)]
+pub struct Name<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Name<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Name
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Name(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Name<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Name<R> {
+    pub fn ident_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::IdentToken))
+            .nth(0)
+    }
+}
+
+/// `Binary = lhs:Expr op:('+' | '-') rhs:Expr`
+#[derive(Clone)]
+pub struct Binary<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Binary<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Binary
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Binary(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Binary<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Binary<R> {
+    pub fn lhs(&self) -> Option<Expr<R>> {
+        self.0.children_of().nth(0)
+    }
+    pub fn op(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::Plus | SyntaxKind::Minus))
+            .nth(0)
+    }
+    pub fn rhs(&self) -> Option<Expr<R>> {
+        self.0.children_of().nth(1)
+    }
+}
+
+/// `Paren = '(' Expr ')'`
+#[derive(Clone)]
+pub struct Paren<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Paren<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Paren
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Paren(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Paren<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Paren<R> {
+    pub fn l_paren_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::LParen))
+            .nth(0)
+    }
+    pub fn expr(&self) -> Option<Expr<R>> {
+        self.0.children_of().nth(0)
+    }
+    pub fn r_paren_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::RParen))
+            .nth(0)
+    }
+}
+
+/// `Call = callee:Expr '(' args:Expr (',' args:Expr)* ')'`
+#[derive(Clone)]
+pub struct Call<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Call<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Call
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Call(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Call<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Call<R> {
+    pub fn callee(&self) -> Option<Expr<R>> {
+        self.0.children_of().nth(0)
+    }
+    pub fn l_paren_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::LParen))
+            .nth(0)
+    }
+    pub fn args(&self) -> impl Iterator<Item = Expr<R>> {
+        self.0.children_of().skip(1)
+    }
+    pub fn comma_tokens(&self) -> impl Iterator<Item = ::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::Comma))
+    }
+    pub fn r_paren_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::RParen))
+            .nth(0)
+    }
+}
+
+/// `Let = 'let' name:Name '=' value:Expr ';'`
+#[derive(Clone)]
+pub struct Let<R: ::rowan2::TreeRoot<SyntaxKind>>(::rowan2::Node<SyntaxKind, R>);
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::rowan2::AstNode<SyntaxKind, R> for Let<R> {
+    fn can_cast(kind: SyntaxKind) -> bool {
+        kind == SyntaxKind::Let
+    }
+    fn cast(node: ::rowan2::Node<SyntaxKind, R>) -> Option<Self> {
+        if Self::can_cast(node.kind()) { Some(Let(node)) } else { None }
+    }
+    fn syntax(&self) -> &::rowan2::Node<SyntaxKind, R> {
+        &self.0
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> ::std::fmt::Debug for Let<R> {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        ::std::fmt::Debug::fmt(&self.0, f)
+    }
+}
+impl<R: ::rowan2::TreeRoot<SyntaxKind>> Let<R> {
+    pub fn let_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::LetToken))
+            .nth(0)
+    }
+    pub fn name(&self) -> Option<Name<R>> {
+        self.0.children_of().nth(0)
+    }
+    pub fn eq_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::Eq))
+            .nth(0)
+    }
+    pub fn value(&self) -> Option<Expr<R>> {
+        self.0.children_of().nth(1)
+    }
+    pub fn semi_token(&self) -> Option<::rowan2::Node<SyntaxKind, R>> {
+        self.0.children().filter(|node| matches!(node.kind(), SyntaxKind::Semi))
+            .nth(0)
+    }
+}
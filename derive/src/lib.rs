@@ -0,0 +1,240 @@
+//! Derive macro for the `SyntaxKind` trait of rowan2. Use it through rowan2
+//! with the `derive` feature enabled, instead of depending on this crate
+//! directly.
+
+extern crate proc_macro;
+extern crate proc_macro2;
+#[macro_use]
+extern crate quote;
+extern crate syn;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use syn::{Data, DeriveInput, Error, Expr, ExprLit, ExprUnary, Fields, Ident, Lit, LitStr, UnOp};
+
+/// A variant of the derived enum, together with its attributes
+struct Variant {
+    ident: Ident,
+    name: String,
+    trivia: bool,
+    token: bool
+}
+
+/// Read the `#[syntax(...)]` attributes of a variant
+fn parse_variant(variant: &syn::Variant) -> Result<Variant, Error> {
+    if !matches!(variant.fields, Fields::Unit) {
+        return Err(Error::new_spanned(variant, "SyntaxKind can only be derived for variants without fields"));
+    }
+    let mut parsed = Variant {
+        ident: variant.ident.clone(),
+        name: variant.ident.to_string(),
+        trivia: false,
+        token: false
+    };
+    for attr in &variant.attrs {
+        if !attr.path().is_ident("syntax") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("trivia") {
+                parsed.trivia = true;
+            } else if meta.path.is_ident("token") {
+                parsed.token = true;
+            } else if meta.path.is_ident("name") {
+                parsed.name = meta.value()?.parse::<LitStr>()?.value();
+            } else {
+                return Err(meta.error("expected `trivia`, `token` or `name = \"...\"`"));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// Read the integer type from `#[repr(...)]`, making sure it fits in a u16.
+/// Return whether it's signed.
+fn parse_repr(input: &DeriveInput) -> Result<bool, Error> {
+    let mut signed = false;
+    for attr in &input.attrs {
+        if !attr.path().is_ident("repr") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            let ident = match meta.path.get_ident() {
+                Some(ident) => ident.to_string(),
+                None => return Ok(())
+            };
+            match &*ident {
+                "u8" | "u16" => (),
+                "i8" | "i16" => signed = true,
+                "u32" | "u64" | "u128" | "usize" | "i32" | "i64" | "i128" | "isize" => {
+                    return Err(meta.error(format!("SyntaxKind uses u16 for raw values, so the enum can't be repr({})", ident)));
+                },
+                _ => if meta.input.peek(syn::token::Paren) {
+                    // Skip arguments, like in `align(2)`
+                    meta.input.parse::<proc_macro2::Group>()?;
+                }
+            }
+            Ok(())
+        })?;
+    }
+    Ok(signed)
+}
+
+/// Get the value of a discriminant, if it's an integer literal
+fn discriminant(expr: &Expr) -> Option<Result<i128, Error>> {
+    match *expr {
+        Expr::Lit(ExprLit { lit: Lit::Int(ref int), .. }) => Some(int.base10_parse()),
+        Expr::Unary(ExprUnary { op: UnOp::Neg(_), ref expr, .. }) => discriminant(expr).map(|value| value.map(|value| -value)),
+        Expr::Group(ref group) => discriminant(&group.expr),
+        Expr::Paren(ref paren) => discriminant(&paren.expr),
+        _ => None
+    }
+}
+
+/// Make sure all discriminants that are known fit in a u16. Discriminants
+/// that are constants can't be checked here, so they are up to the user.
+fn check_discriminants(data: &syn::DataEnum, signed: bool) -> Result<(), Error> {
+    let min = if signed { i128::from(i16::MIN) } else { 0 };
+    let max = if signed { i128::from(i16::MAX) } else { i128::from(u16::MAX) };
+    let mut next = Some(0);
+    for variant in &data.variants {
+        if let Some((_, ref expr)) = variant.discriminant {
+            next = discriminant(expr).transpose()?;
+        }
+        if let Some(value) = next {
+            if value < min || value > max {
+                return Err(Error::new_spanned(
+                    &variant.ident,
+                    format!("the discriminant of {} is {}, which doesn't fit in a u16", variant.ident, value)
+                ));
+            }
+        }
+        next = next.map(|value| value + 1);
+    }
+    Ok(())
+}
+
+/// Return an expression that is true if `self` is one of the variants
+fn is_any_of(ty: &Ident, variants: &[&Variant]) -> TokenStream2 {
+    if variants.is_empty() {
+        return quote!(false);
+    }
+    let idents = variants.iter().map(|variant| &variant.ident);
+    quote!(matches!(self, #(#ty::#idents)|*))
+}
+
+fn derive(input: DeriveInput) -> Result<TokenStream2, Error> {
+    let data = match input.data {
+        Data::Enum(ref data) => data,
+        _ => return Err(Error::new_spanned(&input.ident, "SyntaxKind can only be derived for enums"))
+    };
+    if data.variants.is_empty() {
+        return Err(Error::new_spanned(&input.ident, "SyntaxKind can't be derived for enums without variants"));
+    }
+    let signed = parse_repr(&input)?;
+    check_discriminants(data, signed)?;
+    let variants = data.variants.iter().map(parse_variant).collect::<Result<Vec<_>, _>>()?;
+    for (i, variant) in variants.iter().enumerate() {
+        if variants[..i].iter().any(|other| other.name == variant.name) {
+            return Err(Error::new_spanned(&variant.ident, format!("two kinds are both named {:?}", variant.name)));
+        }
+    }
+
+    let ty = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let idents: Vec<_> = variants.iter().map(|variant| &variant.ident).collect();
+    let names: Vec<_> = variants.iter().map(|variant| &variant.name).collect();
+    let trivia = is_any_of(ty, &variants.iter().filter(|variant| variant.trivia).collect::<Vec<_>>());
+    let token = is_any_of(ty, &variants.iter().filter(|variant| variant.token).collect::<Vec<_>>());
+
+    Ok(quote! {
+        impl #impl_generics ::rowan2::SyntaxKind for #ty #ty_generics #where_clause {
+            fn name(self) -> &'static str {
+                match self {
+                    #(#ty::#idents => #names,)*
+                }
+            }
+            fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    #(#names => Some(#ty::#idents),)*
+                    _ => None
+                }
+            }
+            fn is_trivia(self) -> bool {
+                #trivia
+            }
+            fn is_token(self) -> bool {
+                #token
+            }
+            fn into_raw(self) -> u16 {
+                self as u16
+            }
+            fn from_raw(raw: u16) -> Option<Self> {
+                #(
+                    if raw == #ty::#idents as u16 {
+                        return Some(#ty::#idents);
+                    }
+                )*
+                None
+            }
+        }
+    })
+}
+
+/// Derive `SyntaxKind` for an enum without fields. Variants can be marked
+/// with `#[syntax(trivia)]` and `#[syntax(token)]`, and renamed with
+/// `#[syntax(name = "...")]`.
+#[proc_macro_derive(SyntaxKind, attributes(syntax))]
+pub fn derive_syntax_kind(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    derive(input)
+        .unwrap_or_else(|err| {
+            // Not using `Error::to_compile_error`, since it refers to
+            // `::core` which doesn't exist in crates using Rust 2015
+            let message = err.to_string();
+            quote_spanned!(err.span()=> compile_error!(#message);)
+        })
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::derive;
+
+    fn error(input: syn::DeriveInput) -> String {
+        derive(input).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn reprs() {
+        assert!(derive(syn::parse_quote!(#[repr(u8)] enum Kind { A })).is_ok());
+        assert!(derive(syn::parse_quote!(#[repr(C, i16)] enum Kind { A = -1 })).is_ok());
+        assert_eq!(
+            error(syn::parse_quote!(#[repr(u32)] enum Kind { A })),
+            "SyntaxKind uses u16 for raw values, so the enum can't be repr(u32)"
+        );
+        assert_eq!(
+            error(syn::parse_quote!(#[repr(align(4), usize)] enum Kind { A })),
+            "SyntaxKind uses u16 for raw values, so the enum can't be repr(usize)"
+        );
+    }
+
+    #[test]
+    fn discriminants() {
+        assert!(derive(syn::parse_quote!(enum Kind { A = 0xfffe, B })).is_ok());
+        assert!(derive(syn::parse_quote!(enum Kind { A = SOME_CONST, B })).is_ok());
+        assert_eq!(
+            error(syn::parse_quote!(enum Kind { A = 0xffff, B })),
+            "the discriminant of B is 65536, which doesn't fit in a u16"
+        );
+        assert_eq!(
+            error(syn::parse_quote!(enum Kind { A = -1 })),
+            "the discriminant of A is -1, which doesn't fit in a u16"
+        );
+        assert_eq!(
+            error(syn::parse_quote!(#[repr(i16)] enum Kind { A = 40000 })),
+            "the discriminant of A is 40000, which doesn't fit in a u16"
+        );
+    }
+}
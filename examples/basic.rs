@@ -1,6 +1,7 @@
+#[macro_use]
 extern crate rowan2;
 
-use rowan2::{Node, TreeBuilder};
+use rowan2::Node;
 
 #[derive(Clone, Copy, Debug)]
 enum SyntaxKind {
@@ -17,23 +18,21 @@ fn recurse<R: rowan2::TreeRoot<SyntaxKind>>(indent: usize, node: Node<SyntaxKind
     }
 }
 fn main() {
-    let mut builder = TreeBuilder::new();
-
-    builder.start_internal(SyntaxKind::Group);
-    builder.start_internal(SyntaxKind::Group);
-    builder.leaf(SyntaxKind::Number, "1".into());
-    builder.leaf(SyntaxKind::Operation, "+".into());
-    builder.start_internal(SyntaxKind::Group);
-    builder.leaf(SyntaxKind::Number, "2".into());
-    builder.leaf(SyntaxKind::Operation, "*".into());
-    builder.leaf(SyntaxKind::Number, "3".into());
-    builder.finish_internal();
-    builder.finish_internal();
-    builder.leaf(SyntaxKind::Operation, "-".into());
-    builder.leaf(SyntaxKind::Number, "4".into());
-    builder.finish_internal();
-
-    let node = builder.finish();
+    let node = tree! { SyntaxKind:
+        Group {
+            Group {
+                Number "1",
+                Operation "+",
+                Group {
+                    Number "2",
+                    Operation "*",
+                    Number "3"
+                }
+            },
+            Operation "-",
+            Number "4"
+        }
+    };
 
     recurse(0, node.borrowed());
 
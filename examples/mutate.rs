@@ -1,7 +1,6 @@
+#[macro_use]
 extern crate rowan2;
 
-use rowan2::TreeBuilder;
-
 #[derive(Clone, Copy, Debug)]
 enum SyntaxKind {
     Group,
@@ -11,29 +10,25 @@ enum SyntaxKind {
 }
 
 fn main() {
-    let mut builder = TreeBuilder::new();
-
-    builder.start_internal(SyntaxKind::Group);
-
-    builder.start_internal(SyntaxKind::Group);
-    builder.leaf(SyntaxKind::Paren, "(".into());
-    builder.leaf(SyntaxKind::Number, "1".into());
-    builder.leaf(SyntaxKind::Operation, "+".into());
-    builder.start_internal(SyntaxKind::Group);
-    builder.leaf(SyntaxKind::Paren, "(".into());
-    builder.leaf(SyntaxKind::Number, "2".into());
-    builder.leaf(SyntaxKind::Operation, "*".into());
-    builder.leaf(SyntaxKind::Number, "3".into());
-    builder.leaf(SyntaxKind::Paren, ")".into());
-    builder.finish_internal();
-    builder.leaf(SyntaxKind::Paren, ")".into());
-    builder.finish_internal();
-    builder.leaf(SyntaxKind::Operation, "-".into());
-    builder.leaf(SyntaxKind::Number, "4".into());
-
-    builder.finish_internal();
-
-    let node = builder.finish_mut();
+    let node = tree! { mut SyntaxKind:
+        Group {
+            Group {
+                Paren "(",
+                Number "1",
+                Operation "+",
+                Group {
+                    Paren "(",
+                    Number "2",
+                    Operation "*",
+                    Number "3",
+                    Paren ")"
+                },
+                Paren ")"
+            },
+            Operation "-",
+            Number "4"
+        }
+    };
 
     let group = node.first_child().unwrap();
     let paren = group.first_child().unwrap();
@@ -44,4 +39,9 @@ fn main() {
     op.remove();
 
     println!("{}", node);
+
+    let node = node.freeze();
+    for (nested, event) in node.walk() {
+        println!("{:indent$}{:?}", "", event, indent = nested * 2);
+    }
 }
@@ -0,0 +1,41 @@
+use crate::node::{Node, TreeRoot};
+
+/// A typed wrapper around nodes of certain kinds, for building a typed AST
+/// on top of the untyped tree. Implement this generically over the root, like
+/// `impl<R: TreeRoot<Kind>> AstNode<Kind, R> for Function<R>`, so the same
+/// wrapper can be used with all kinds of trees.
+pub trait AstNode<T: Copy, R: TreeRoot<T>>: Sized {
+    /// Return true if nodes of this kind can be wrapped in this type
+    fn can_cast(kind: T) -> bool;
+    /// Wrap the node in this type, if its kind is supported
+    fn cast(node: Node<T, R>) -> Option<Self>;
+    /// Get the underlying node
+    fn syntax(&self) -> &Node<T, R>;
+}
+impl<T: Copy, R: TreeRoot<T>> AstNode<T, R> for Node<T, R> {
+    fn can_cast(_kind: T) -> bool {
+        true
+    }
+    fn cast(node: Node<T, R>) -> Option<Self> {
+        Some(node)
+    }
+    fn syntax(&self) -> &Node<T, R> {
+        self
+    }
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Wrap this node in the AST type, if its kind is supported. This is the
+    /// same as `N::cast(node)`.
+    pub fn cast<N: AstNode<T, R>>(self) -> Option<N> {
+        N::cast(self)
+    }
+    /// Get the first child that can be wrapped in the AST type
+    pub fn child_of<N: AstNode<T, R>>(&self) -> Option<N> {
+        self.children_of().next()
+    }
+    /// Get an iterator over all children that can be wrapped in the AST type
+    pub fn children_of<N: AstNode<T, R>>(&self) -> impl Iterator<Item = N> {
+        self.children().filter_map(N::cast)
+    }
+}
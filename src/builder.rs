@@ -1,10 +1,21 @@
 use crate::node::{MutableRoot, Node, RootData, OwnedRoot};
 use smol_str::SmolStr;
 
+/// A node ID consists of the index into the arena and the generation of the
+/// slot at that index. Every time a slot is freed its generation is bumped,
+/// so IDs of removed nodes never point to a node that later reuses the slot.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
-pub(crate) struct NodeId(pub(crate) usize);
+pub(crate) struct NodeId {
+    pub(crate) index: usize,
+    pub(crate) generation: u32
+}
+impl NodeId {
+    pub(crate) fn new(index: usize) -> Self {
+        NodeId { index, generation: 0 }
+    }
+}
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub(crate) enum Content {
     Branch(Option<NodeId>),
     Leaf(SmolStr)
@@ -18,14 +29,22 @@ impl Content {
     }
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+pub(crate) struct Slot<T: Copy> {
+    pub(crate) generation: u32,
+    pub(crate) repr: Option<NodeRepr<T>>
+}
+
+#[derive(Clone, Debug)]
 pub(crate) struct NodeRepr<T: Copy> {
     pub(crate) kind: T,
 
     pub(crate) parent: Option<NodeId>,
     pub(crate) prev_sibling: Option<NodeId>,
     pub(crate) next_sibling: Option<NodeId>,
-    pub(crate) content: Content
+    pub(crate) content: Content,
+    /// The text length of this node and all its children
+    pub(crate) len: u32
 }
 
 /// See the function `checkpoint` in `TreeBuilder`
@@ -38,7 +57,7 @@ pub struct Checkpoint {
 /// A builder for trees, supplying functions for starting/ending branches
 #[derive(Debug)]
 pub struct TreeBuilder<T: Copy> {
-    arena: Vec<Option<NodeRepr<T>>>,
+    arena: Vec<Slot<T>>,
     parent: Option<NodeId>,
     child: Option<NodeId>,
 
@@ -63,7 +82,7 @@ impl<T: Copy> TreeBuilder<T> {
         Self::default()
     }
     fn get(&mut self, id: Option<NodeId>) -> Option<&mut NodeRepr<T>> {
-        id.map(move |id| self.arena[id.0].as_mut().unwrap())
+        id.map(move |id| self.arena[id.index].repr.as_mut().unwrap())
     }
     fn parent(&mut self) -> Option<&mut NodeRepr<T>> {
         let id = self.parent; self.get(id)
@@ -72,18 +91,23 @@ impl<T: Copy> TreeBuilder<T> {
         let id = self.child; self.get(id)
     }
     fn insert(&mut self, node: NodeRepr<T>) -> NodeId {
-        let id = NodeId(self.arena.len());
-        self.arena.push(Some(node));
+        let id = NodeId::new(self.arena.len());
+        self.arena.push(Slot { generation: 0, repr: Some(node) });
         id
     }
     fn insert_and_update(&mut self, kind: T, content: Content) -> NodeId {
+        let len = match content {
+            Content::Branch(_) => 0,
+            Content::Leaf(ref text) => text.len() as u32
+        };
         let node = NodeRepr {
             kind,
 
             parent: self.parent,
             prev_sibling: self.child,
             next_sibling: None,
-            content
+            content,
+            len
         };
         let id = self.insert(node);
 
@@ -108,10 +132,12 @@ impl<T: Copy> TreeBuilder<T> {
     /// End a previously started branch
     pub fn finish_internal(&mut self) {
         if let Some(parent) = self.parent {
-            let end = self.child.map(|id| self.ranges[id.0].1.unwrap())
-                .unwrap_or(self.ranges[parent.0].0);
+            let end = self.child.map(|id| self.ranges[id.index].1.unwrap())
+                .unwrap_or(self.ranges[parent.index].0);
             // Update the end position of the range
-            self.ranges[parent.0].1 = Some(end);
+            self.ranges[parent.index].1 = Some(end);
+            let len = end - self.ranges[parent.index].0;
+            self.parent().unwrap().len = len;
         }
 
         self.child = self.parent;
@@ -151,7 +177,8 @@ impl<T: Copy> TreeBuilder<T> {
                     parent: self.parent,
                     prev_sibling: None,
                     next_sibling: None,
-                    content: Content::Branch(old_id)
+                    content: Content::Branch(old_id),
+                    len: 0
                 };
                 let id = self.insert(node);
                 while let Some(old) = self.get(old_id) {
@@ -172,7 +199,8 @@ impl<T: Copy> TreeBuilder<T> {
                     parent: self.get(previous).unwrap().parent,
                     prev_sibling: previous,
                     next_sibling: None,
-                    content: Content::Branch(old_id)
+                    content: Content::Branch(old_id),
+                    len: 0
                 };
                 let id = self.insert(node);
                 if let Some(old) = self.get(old_id) {
@@ -193,24 +221,18 @@ impl<T: Copy> TreeBuilder<T> {
         assert!(self.child().unwrap().prev_sibling.is_none(), "can't finish on more than one node");
 
         Node::new_root(
-            RootData {
-                arena: self.arena,
-                ranges: self.ranges
-            },
+            RootData::new(self.arena, self.ranges),
             self.child.unwrap()
         )
     }
-    /// Build the tree, returning an mutable owned tree with all ranges
-    /// discarded
+    /// Build the tree, returning an mutable owned tree. Mutable trees don't
+    /// store ranges, they calculate them from the length of each node
     pub fn finish_mut(mut self) -> Node<T, MutableRoot<T>> {
         assert!(self.child.is_some(), "finish called on empty builder");
         assert!(self.child().unwrap().prev_sibling.is_none(), "can't finish on more than one node");
 
         Node::new_root_mut(
-            RootData {
-                arena: self.arena,
-                ranges: Vec::new()
-            },
+            RootData::new(self.arena, Vec::new()),
             self.child.unwrap()
         )
     }
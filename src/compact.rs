@@ -0,0 +1,181 @@
+use crate::{
+    builder::{Content, NodeId, Slot},
+    node::{MutableRoot, Node, TreeRoot}
+};
+
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+    mem
+};
+
+/// The result of `Node::compact`, which maps nodes from before the compaction
+/// to their new location in the arena
+pub struct Compaction<T: Copy> {
+    root: MutableRoot<T>,
+    /// For each old arena index, the old generation and the new ID
+    map: Vec<Option<(u32, NodeId)>>
+}
+impl<T: Copy> Compaction<T> {
+    /// Get the new handle of a node from before the compaction. Returns None
+    /// if the node had already been removed or belongs to another tree.
+    pub fn get(&self, node: &Node<T, MutableRoot<T>>) -> Option<Node<T, MutableRoot<T>>> {
+        if node.root_id() != self.root.root_id() {
+            return None;
+        }
+        let (generation, new) = self.map.get(node.node.index).cloned()??;
+        if generation != node.node.generation {
+            return None;
+        }
+        Some(Node {
+            root: self.root.clone(),
+            node: new,
+            _marker: PhantomData
+        })
+    }
+    /// Return the number of nodes that were kept
+    pub fn len(&self) -> usize {
+        self.map.iter().filter(|entry| entry.is_some()).count()
+    }
+    /// Return true if no nodes were kept, which happens when the root has been
+    /// removed
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+impl<T: Copy> Debug for Compaction<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Compaction")
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+impl<T: Copy> Node<T, MutableRoot<T>> {
+    /// Compact the arena of this tree. All nodes that can't be reached from
+    /// the root are dropped and all slots freed by `remove` are reclaimed. The
+    /// remaining nodes are placed in document order, making traversal more
+    /// cache friendly.
+    ///
+    /// This invalidates all existing nodes of the tree, including this one.
+    /// They stay invalid even after new nodes are added to the smaller arena.
+    /// Use the returned `Compaction` to look up where they went.
+    ///
+    /// # Panics
+    /// This function panics if this node has been removed. Any other node of
+    /// the tree can be used instead.
+    pub fn compact(&self) -> Compaction<T> {
+        let mut data = self.data_mut();
+        let mut map = vec![None; data.arena.len()];
+        let mut order = Vec::new();
+
+        // Move all slots to a generation no handle of this tree can hold yet,
+        // so handles from before the compaction can't point to a node that
+        // moved into their slot or to one allocated later at a truncated index
+        let generation = data.arena.iter()
+            .map(|slot| slot.generation)
+            .max()
+            .map_or(data.generation, |max| max.max(data.generation).wrapping_add(1));
+        data.generation = generation;
+
+        // Find the first top-level node and walk all nodes in document order
+        let mut first = self.node;
+        while let Some(next) = data.repr(first).parent.or(data.repr(first).prev_sibling) {
+            first = next;
+        }
+        let mut next = Some(first);
+        'outer: while let Some(current) = next {
+            map[current.index] = Some((current.generation, NodeId {
+                index: order.len(),
+                generation
+            }));
+            order.push(current);
+
+            if let Content::Branch(Some(child)) = data.repr(current).content {
+                next = Some(child);
+                continue;
+            }
+            let mut current = current;
+            loop {
+                let repr = data.repr(current);
+                if repr.next_sibling.is_some() {
+                    next = repr.next_sibling;
+                    continue 'outer;
+                }
+                match repr.parent {
+                    Some(parent) => current = parent,
+                    None => break 'outer
+                }
+            }
+        }
+
+        let mut old = mem::take(&mut data.arena);
+        data.free.clear();
+
+        let translate = |id: Option<NodeId>| id.map(|id| map[id.index].unwrap().1);
+        data.arena = order.iter()
+            .map(|id| {
+                let slot = &mut old[id.index];
+                let mut repr = slot.repr.take().unwrap();
+                repr.parent = translate(repr.parent);
+                repr.prev_sibling = translate(repr.prev_sibling);
+                repr.next_sibling = translate(repr.next_sibling);
+                if let Content::Branch(ref mut child) = repr.content {
+                    *child = translate(*child);
+                }
+                Slot {
+                    generation: map[id.index].unwrap().1.generation,
+                    repr: Some(repr)
+                }
+            })
+            .collect();
+
+        Compaction {
+            root: self.root.clone(),
+            map
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{builder::TreeBuilder, node::TreeRoot};
+
+    #[test]
+    fn arena_is_in_document_order() {
+        let mut builder = TreeBuilder::new();
+        builder.start_internal(0);
+        builder.leaf(1, "1".into());
+        builder.leaf(2, "+".into());
+        builder.start_internal(0);
+        builder.leaf(1, "2".into());
+        builder.leaf(2, "*".into());
+        builder.leaf(1, "3".into());
+        builder.finish_internal();
+        builder.finish_internal();
+        let root = builder.finish_mut();
+
+        // Move nodes around in the arena by removing some and reusing their
+        // slots elsewhere
+        let inner = root.last_child().unwrap();
+        inner.first_child().unwrap().remove();
+        root.first_child().unwrap().remove();
+        inner.append_child(2, Some("-".into()));
+        root.prepend_child(0, None);
+        root.first_child().unwrap().append_child(1, Some("4".into()));
+
+        let compaction = root.compact();
+        let root = compaction.get(&root).unwrap();
+        let nodes: Vec<_> = root.descendants().collect();
+        assert_eq!(nodes.len(), compaction.len());
+        root.root.with_data(|data| {
+            assert_eq!(data.arena.len(), nodes.len());
+            assert!(data.free.is_empty());
+            for (index, node) in nodes.iter().enumerate() {
+                assert_eq!(node.node.index, index);
+                assert!(data.arena[index].repr.is_some());
+            }
+        });
+        assert_eq!(root.to_string(), "4+*3-");
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    builder::{Content, NodeId, NodeRepr},
+    node::{Node, OwnedRoot, RefRoot, RootData, TreeRoot}
+};
+
+use smol_str::SmolStr;
+use text_unit::{TextRange, TextUnit};
+
+/// A cursor for quickly moving around a tree. Unlike nodes, a cursor only
+/// borrows the tree data once, so moving it around doesn't involve any
+/// reference counting or locking. It keeps track of its offset as it moves,
+/// so getting the range is cheap even on mutable trees.
+pub struct TreeCursor<'a, T: Copy + 'a> {
+    data: &'a RootData<T>,
+    node: NodeId,
+    offset: u32
+}
+impl<'a, T: Copy> TreeCursor<'a, T> {
+    fn new(data: &'a RootData<T>, node: NodeId) -> Self {
+        Self {
+            data,
+            node,
+            offset: data.range(node).start().into()
+        }
+    }
+    fn repr(&self) -> &'a NodeRepr<T> {
+        self.data.repr(self.node)
+    }
+    /// Get the kind of the current node
+    pub fn kind(&self) -> T {
+        self.repr().kind
+    }
+    /// Get the leaf text of the current node
+    pub fn leaf_text(&self) -> Option<&'a SmolStr> {
+        match self.repr().content {
+            Content::Branch(_) => None,
+            Content::Leaf(ref text) => Some(text)
+        }
+    }
+    /// Return true if the current node is a leaf
+    pub fn is_leaf(&self) -> bool {
+        self.leaf_text().is_some()
+    }
+    /// Get the range of the current node
+    pub fn range(&self) -> TextRange {
+        TextRange::offset_len(TextUnit::from(self.offset), TextUnit::from(self.repr().len))
+    }
+    /// Move to the parent, returning false if there is none. This isn't
+    /// limited to the node the cursor was created at, it can move all the way
+    /// up to the root of the tree.
+    pub fn goto_parent(&mut self) -> bool {
+        let parent = match self.repr().parent {
+            Some(parent) => parent,
+            None => return false
+        };
+        let mut prev = self.repr().prev_sibling;
+        while let Some(sibling) = prev {
+            let repr = self.data.repr(sibling);
+            self.offset -= repr.len;
+            prev = repr.prev_sibling;
+        }
+        self.node = parent;
+        true
+    }
+    /// Move to the first child, returning false if there is none
+    pub fn goto_first_child(&mut self) -> bool {
+        match self.repr().content {
+            Content::Branch(Some(child)) => {
+                self.node = child;
+                true
+            },
+            _ => false
+        }
+    }
+    /// Move to the next sibling, returning false if there is none
+    pub fn goto_next_sibling(&mut self) -> bool {
+        match self.repr().next_sibling {
+            Some(next) => {
+                self.offset += self.repr().len;
+                self.node = next;
+                true
+            },
+            None => false
+        }
+    }
+    /// Move to the previous sibling, returning false if there is none
+    pub fn goto_prev_sibling(&mut self) -> bool {
+        match self.repr().prev_sibling {
+            Some(prev) => {
+                self.node = prev;
+                self.offset -= self.repr().len;
+                true
+            },
+            None => false
+        }
+    }
+}
+
+impl<'a, T: Copy> Node<T, RefRoot<'a, T, OwnedRoot<T>>> {
+    /// Create a cursor at this node, with the same lifetime as the borrowed
+    /// tree. The cursor can move anywhere in the tree, including above this
+    /// node using `goto_parent`.
+    pub fn cursor(self) -> TreeCursor<'a, T> {
+        let data = self.root.inner.borrow_data().unwrap();
+        TreeCursor::new(data, self.node)
+    }
+}
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Call the function with a cursor at this node. This works with all tree
+    /// roots, and only locks mutable ones once. Like with `cursor`, the cursor
+    /// isn't limited to the subtree of this node.
+    pub fn with_cursor<F, V>(&self, f: F) -> V
+        where F: FnOnce(&mut TreeCursor<T>) -> V
+    {
+        self.root.with_data(|data| f(&mut TreeCursor::new(data, self.node)))
+    }
+}
@@ -0,0 +1,135 @@
+use crate::node::{Node, OwnedRoot, TreeRoot, WalkEvent};
+
+/// A cache of values calculated by `Node::fold_cached`. The cache keeps the
+/// tree it was used on alive, and is cleared automatically if it's used on a
+/// different tree. Only use one cache per pair of fold functions, or the
+/// values will get mixed up.
+pub struct FoldCache<T: Copy, V> {
+    root: Option<OwnedRoot<T>>,
+    values: Vec<Option<V>>
+}
+impl<T: Copy, V> Default for FoldCache<T, V> {
+    fn default() -> Self {
+        Self {
+            root: None,
+            values: Vec::new()
+        }
+    }
+}
+impl<T: Copy, V> FoldCache<T, V> {
+    /// Create a new empty cache
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Remove all values from the cache
+    pub fn clear(&mut self) {
+        self.root = None;
+        self.values.clear();
+    }
+    /// Get the cached value of a node, if any
+    pub fn get(&self, node: &Node<T, OwnedRoot<T>>) -> Option<&V> {
+        match self.root {
+            Some(ref root) if root.root_id() == node.root_id() => (),
+            _ => return None
+        }
+        self.values.get(node.node.index).and_then(Option::as_ref)
+    }
+}
+
+/// Where `Node::fold_with` looks up and stores the values of nodes
+trait Memo<V> {
+    fn get(&self, index: usize) -> Option<V>;
+    fn set(&mut self, index: usize, value: &V);
+}
+impl<V> Memo<V> for () {
+    fn get(&self, _index: usize) -> Option<V> {
+        None
+    }
+    fn set(&mut self, _index: usize, _value: &V) {}
+}
+impl<V: Clone> Memo<V> for Vec<Option<V>> {
+    fn get(&self, index: usize) -> Option<V> {
+        self[index].clone()
+    }
+    fn set(&mut self, index: usize, value: &V) {
+        self[index] = Some(value.clone());
+    }
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Fold this subtree into a value, bottom-up. Subtrees with a value in the
+    /// memo aren't walked, and all newly calculated values are stored in it.
+    fn fold_with<V, L, B, M>(&self, mut leaf: L, mut branch: B, memo: &mut M) -> V
+        where L: FnMut(&Self) -> V,
+              B: FnMut(&Self, Vec<V>) -> V,
+              M: Memo<V>
+    {
+        // The values of the children of each branch we're in, with an extra
+        // level for the result
+        let mut stack = vec![Vec::new()];
+        let mut skipped = false;
+
+        let mut walker = self.walk();
+        while let Some((_, event)) = walker.next() {
+            match event {
+                WalkEvent::Enter(node) => {
+                    let value = match memo.get(node.node.index) {
+                        Some(value) => value,
+                        None if node.is_leaf() => {
+                            let value = leaf(&node);
+                            memo.set(node.node.index, &value);
+                            value
+                        },
+                        None => {
+                            stack.push(Vec::new());
+                            continue;
+                        }
+                    };
+                    stack.last_mut().unwrap().push(value);
+                    walker.skip_subtree();
+                    skipped = true;
+                },
+                WalkEvent::Leave(_) if skipped => skipped = false,
+                WalkEvent::Leave(node) => {
+                    let children = stack.pop().unwrap();
+                    let value = branch(&node, children);
+                    memo.set(node.node.index, &value);
+                    stack.last_mut().unwrap().push(value);
+                }
+            }
+        }
+
+        stack.pop().unwrap().pop().unwrap()
+    }
+    /// Calculate a value for this subtree bottom-up. Leaves are turned into
+    /// values using `leaf`, and branches using `branch` together with the
+    /// values of all their children, in order. This doesn't use recursion, so
+    /// it works on trees of any depth.
+    pub fn fold<V, L, B>(&self, leaf: L, branch: B) -> V
+        where L: FnMut(&Self) -> V,
+              B: FnMut(&Self, Vec<V>) -> V
+    {
+        self.fold_with(leaf, branch, &mut ())
+    }
+}
+impl<T: Copy> Node<T, OwnedRoot<T>> {
+    /// Like `fold`, but the value of each node is stored in the cache, and
+    /// subtrees with a cached value aren't walked again. Since owned trees
+    /// can't change, this makes repeated queries on the same tree free.
+    pub fn fold_cached<V, L, B>(&self, cache: &mut FoldCache<T, V>, leaf: L, branch: B) -> V
+        where V: Clone,
+              L: FnMut(&Self) -> V,
+              B: FnMut(&Self, Vec<V>) -> V
+    {
+        match cache.root {
+            Some(ref root) if root.root_id() == self.root_id() => (),
+            _ => {
+                cache.clear();
+                cache.root = Some(self.root.clone());
+            }
+        }
+        let len = self.root.with_data(|data| data.arena.len());
+        cache.values.resize_with(len, || None);
+        self.fold_with(leaf, branch, &mut cache.values)
+    }
+}
@@ -0,0 +1,140 @@
+use crate::{
+    builder::Content,
+    node::{Node, TreeRoot, WalkEvent}
+};
+
+use std::iter;
+
+/// The direction to go in, see `Node::siblings`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Next,
+    Prev
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Return true if this node is a leaf. Unlike checking `leaf_text_cow`,
+    /// this never clones the text.
+    pub fn is_leaf(&self) -> bool {
+        self.root.with_data(|data| match data.repr(self.node).content {
+            Content::Branch(_) => false,
+            Content::Leaf(_) => true
+        })
+    }
+    /// Get the last child
+    pub fn last_child(&self) -> Option<Self> {
+        let mut child = self.first_child()?;
+        while let Some(next) = child.next_sibling() {
+            child = next;
+        }
+        Some(child)
+    }
+    /// Get an iterator over all children, starting with the last one
+    pub fn children_rev(&self) -> impl Iterator<Item = Self> {
+        iter::successors(self.last_child(), Self::prev_sibling)
+    }
+    /// Get an iterator over all parents of this node, from the closest one up
+    /// to the root. This node itself isn't included.
+    pub fn ancestors(&self) -> impl Iterator<Item = Self> {
+        iter::successors(self.parent(), Self::parent)
+    }
+    /// Get an iterator over this node and all siblings in the specified
+    /// direction
+    pub fn siblings(&self, direction: Direction) -> impl Iterator<Item = Self> {
+        iter::successors(Some(self.clone()), move |node| match direction {
+            Direction::Next => node.next_sibling(),
+            Direction::Prev => node.prev_sibling()
+        })
+    }
+    /// Get an iterator over this node and all nodes below it, parents before
+    /// children. This is like `walk` but without the `Leave` events.
+    pub fn descendants(&self) -> impl Iterator<Item = Self> {
+        self.walk().filter_map(|(_, event)| match event {
+            WalkEvent::Enter(node) => Some(node),
+            WalkEvent::Leave(_) => None
+        })
+    }
+    /// Get an iterator over all leaves in this subtree, in order. If this node
+    /// is a leaf, that's just this node.
+    pub fn leaves(&self) -> impl Iterator<Item = Self> {
+        self.descendants().filter(Self::is_leaf)
+    }
+    /// The same as `leaves`, for those used to rowan calling them tokens
+    pub fn tokens(&self) -> impl Iterator<Item = Self> {
+        self.leaves()
+    }
+    /// Get the first or last leaf in this subtree, skipping empty branches.
+    /// Doesn't recurse, so it works on arbitrarily deep trees.
+    fn edge_token(&self, direction: Direction) -> Option<Self> {
+        let mut current = self.clone();
+        loop {
+            if current.is_leaf() {
+                return Some(current);
+            }
+            let child = match direction {
+                Direction::Next => current.first_child(),
+                Direction::Prev => current.last_child()
+            };
+            if let Some(child) = child {
+                current = child;
+                continue;
+            }
+            // An empty branch, so climb back up until there's a sibling to
+            // continue with, without leaving this subtree
+            loop {
+                if current == *self {
+                    return None;
+                }
+                let sibling = match direction {
+                    Direction::Next => current.next_sibling(),
+                    Direction::Prev => current.prev_sibling()
+                };
+                match sibling {
+                    Some(sibling) => {
+                        current = sibling;
+                        break;
+                    },
+                    None => current = current.parent()?
+                }
+            }
+        }
+    }
+    /// Get the first leaf in this subtree, skipping empty branches. If this
+    /// node is a leaf, that's just this node.
+    pub fn first_token(&self) -> Option<Self> {
+        self.edge_token(Direction::Next)
+    }
+    /// Get the last leaf in this subtree, skipping empty branches. If this
+    /// node is a leaf, that's just this node.
+    pub fn last_token(&self) -> Option<Self> {
+        self.edge_token(Direction::Prev)
+    }
+    /// Get the leaf directly after this node in the document, going up through
+    /// parents if this is the last node in its branch
+    pub fn next_token(&self) -> Option<Self> {
+        let mut current = self.clone();
+        loop {
+            let token = current.siblings(Direction::Next)
+                .skip(1)
+                .find_map(|sibling| sibling.first_token());
+            if token.is_some() {
+                return token;
+            }
+            current = current.parent()?;
+        }
+    }
+    /// Get the leaf directly before this node in the document, going up
+    /// through parents if this is the first node in its branch
+    pub fn prev_token(&self) -> Option<Self> {
+        let mut current = self.clone();
+        loop {
+            let token = current.siblings(Direction::Prev)
+                .skip(1)
+                .find_map(|sibling| sibling.last_token());
+            if token.is_some() {
+                return token;
+            }
+            current = current.parent()?;
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::node::{Node, TreeRoot, WalkEvent};
+
+use std::fmt::{self, Debug, Display};
+
+/// Information about a node kind, for kind types that are more than just
+/// `Copy`. With the `derive` feature, this can be derived for enums without
+/// fields:
+///
+#[cfg_attr(feature = "derive", doc = "```")]
+#[cfg_attr(not(feature = "derive"), doc = "```ignore")]
+/// #[macro_use]
+/// extern crate rowan2;
+///
+/// use rowan2::SyntaxKind;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq, SyntaxKind)]
+/// enum Kind {
+///     #[syntax(trivia)]
+///     Whitespace,
+///     #[syntax(token, name = "number")]
+///     Number,
+///     Group
+/// }
+///
+/// fn main() {
+///     assert_eq!(Kind::Number.name(), "number");
+///     assert_eq!(Kind::from_name("Group"), Some(Kind::Group));
+///     assert!(Kind::Whitespace.is_trivia());
+///     assert_eq!(Kind::from_raw(Kind::Group.into_raw()), Some(Kind::Group));
+///
+///     let root = tree! { Kind: Group { Number "1", Whitespace " " } };
+///     assert_eq!(root.dump().skip_trivia().to_string(), "Group@[0; 2)\n  number@[0; 1) \"1\"\n");
+///     // `Debug` still uses the `Debug` implementation of the kind
+///     assert_eq!(format!("{:?}", root.first_child().unwrap()), "Number@[0; 1)");
+///     assert_eq!(root.first_child().unwrap().debug_name(), "number@[0; 1)");
+/// }
+/// ```
+///
+/// Variants are named after themselves unless `name` is given, and the raw
+/// value is the discriminant. The enum may have a `u8`, `u16`, `i8` or `i16`
+/// representation, as long as no discriminant is outside of `u16`.
+///
+/// `Debug` for `Node` only requires the kind to be `Debug`, so it keeps
+/// printing kinds that way even if they implement this trait. It can't use
+/// the name only when it's available without specialization, and requiring
+/// this trait would break `Debug` for all other kind types. Use
+/// `Node::debug_name` and `Node::dump` to print kinds by name instead.
+pub trait SyntaxKind: Copy {
+    /// Get the name of this kind
+    fn name(self) -> &'static str;
+    /// Look up a kind by its name. This can be used to resolve kind names in
+    /// `Query::new` and `Replace::new`.
+    fn from_name(name: &str) -> Option<Self>;
+    /// Return true if nodes of this kind are trivia, like whitespace and
+    /// comments
+    fn is_trivia(self) -> bool {
+        false
+    }
+    /// Return true if this is the kind of a token, as opposed to a branch
+    fn is_token(self) -> bool {
+        false
+    }
+    /// Convert this kind into its raw value
+    fn into_raw(self) -> u16;
+    /// Convert a raw value back into a kind, or None if no kind has it
+    fn from_raw(raw: u16) -> Option<Self>;
+}
+
+/// An indented dump of a tree, returned by `Node::dump`
+pub struct TreeDump<'a, T: Copy, R: TreeRoot<T>> {
+    node: &'a Node<T, R>,
+    trivia: bool
+}
+impl<'a, T: SyntaxKind, R: TreeRoot<T>> TreeDump<'a, T, R> {
+    /// Leave out all nodes that are trivia, together with their children
+    pub fn skip_trivia(mut self) -> Self {
+        self.trivia = false;
+        self
+    }
+}
+impl<'a, T: SyntaxKind, R: TreeRoot<T>> Display for TreeDump<'a, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut walker = self.node.walk();
+        while let Some((depth, event)) = walker.next() {
+            let node = match event {
+                WalkEvent::Enter(node) => node,
+                WalkEvent::Leave(_) => continue
+            };
+            let kind = node.kind();
+            if !self.trivia && kind.is_trivia() {
+                walker.skip_subtree();
+                continue;
+            }
+            write!(f, "{:indent$}{}@{:?}", "", kind.name(), node.range(), indent = depth * 2)?;
+            if let Some(text) = node.leaf_text_cow() {
+                write!(f, " {:?}", &**text)?;
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+impl<'a, T: SyntaxKind, R: TreeRoot<T>> Debug for TreeDump<'a, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl<T: SyntaxKind, R: TreeRoot<T>> Node<T, R> {
+    /// Get the name of the node kind
+    pub fn kind_name(&self) -> &'static str {
+        self.kind().name()
+    }
+    /// Return true if the node kind is trivia
+    pub fn is_trivia(&self) -> bool {
+        self.kind().is_trivia()
+    }
+    /// Dump this subtree with one node per line, indented by depth and named
+    /// using `SyntaxKind::name`, like `Number@[0; 1) "1"`
+    pub fn dump(&self) -> TreeDump<'_, T, R> {
+        TreeDump {
+            node: self,
+            trivia: true
+        }
+    }
+    /// Like the `Debug` output, but using `SyntaxKind::name` for the kind
+    pub fn debug_name(&self) -> String {
+        match (self.try_kind(), self.try_range()) {
+            (Ok(kind), Some(range)) => format!("{}@{:?}", kind.name(), range),
+            _ => "REMOVED".into()
+        }
+    }
+}
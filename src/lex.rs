@@ -0,0 +1,26 @@
+use smol_str::SmolStr;
+
+/// Read a string literal, starting right after its opening quote. `\n` and
+/// `\t` are escapes for newlines and tabs, and a backslash before any other
+/// character is replaced by just that character. Returns the string and the
+/// position after the closing quote, or None if the string never ends.
+pub(crate) fn string(source: &str, mut pos: usize) -> Option<(SmolStr, usize)> {
+    let mut string = String::new();
+    let mut chars = source[pos..].chars();
+    loop {
+        let c = chars.next()?;
+        pos += c.len_utf8();
+        match c {
+            '"' => return Some((string.into(), pos)),
+            '\\' => if let Some(c) = chars.next() {
+                pos += c.len_utf8();
+                string.push(match c {
+                    'n' => '\n',
+                    't' => '\t',
+                    c => c
+                });
+            },
+            c => string.push(c)
+        }
+    }
+}
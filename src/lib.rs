@@ -1,12 +1,44 @@
 extern crate smol_str;
 extern crate text_unit;
+#[cfg(feature = "derive")]
+extern crate rowan2_derive;
 
+mod ast;
 mod builder;
+mod compact;
+mod cursor;
+mod fold;
+mod iter;
+mod kind;
+mod lex;
 mod lock;
+mod lookup;
+mod macros;
 mod node;
+mod query;
+mod replace;
+mod rewrite;
+mod structural;
+mod validate;
+mod visit;
 
+pub use ast::*;
 pub use builder::*;
+pub use compact::*;
+pub use cursor::*;
+pub use fold::*;
+pub use iter::*;
+pub use kind::*;
+pub use lookup::*;
 pub use node::*;
+pub use query::*;
+pub use replace::*;
+pub use rewrite::*;
+pub use structural::*;
+pub use validate::*;
+pub use visit::*;
 
+#[cfg(feature = "derive")]
+pub use rowan2_derive::SyntaxKind;
 pub use smol_str::SmolStr;
 pub use text_unit::{TextRange, TextUnit};
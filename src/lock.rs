@@ -5,12 +5,21 @@ mod inner {
         ops::{Deref, DerefMut},
         rc::Rc
     };
+    pub use std::cell::OnceCell as Once;
     #[derive(Debug)]
     pub struct RefCount<T>(Rc<T>);
     impl<T> RefCount<T> {
         pub fn new(inner: T) -> Self {
             RefCount(Rc::new(inner))
         }
+        pub fn as_ptr(this: &Self) -> *const T {
+            Rc::as_ptr(&this.0)
+        }
+    }
+    impl<T: Clone> RefCount<T> {
+        pub fn make_mut(this: &mut Self) -> &mut T {
+            Rc::make_mut(&mut this.0)
+        }
     }
     impl<T> Clone for RefCount<T> {
         fn clone(&self) -> Self {
@@ -44,12 +53,21 @@ mod inner {
         ops::{Deref, DerefMut},
         sync::{Arc, RwLock}
     };
+    pub use std::sync::OnceLock as Once;
     #[derive(Debug)]
     pub struct RefCount<T>(Arc<T>);
     impl<T> RefCount<T> {
         pub fn new(inner: T) -> Self {
             RefCount(Arc::new(inner))
         }
+        pub fn as_ptr(this: &Self) -> *const T {
+            Arc::as_ptr(&this.0)
+        }
+    }
+    impl<T: Clone> RefCount<T> {
+        pub fn make_mut(this: &mut Self) -> &mut T {
+            Arc::make_mut(&mut this.0)
+        }
     }
     impl<T> Clone for RefCount<T> {
         fn clone(&self) -> Self {
@@ -0,0 +1,118 @@
+use crate::node::{Node, TreeRoot};
+
+use text_unit::{TextRange, TextUnit};
+
+/// The result of `Node::token_at_offset`. An offset can either be outside of
+/// the tree, inside of a leaf, or right between two leaves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TokenAtOffset<N> {
+    None,
+    Single(N),
+    Between(N, N)
+}
+impl<N> TokenAtOffset<N> {
+    /// Convert the result to the leaf to the left if the offset is between two
+    /// leaves
+    pub fn left_biased(self) -> Option<N> {
+        match self {
+            TokenAtOffset::None => None,
+            TokenAtOffset::Single(node) => Some(node),
+            TokenAtOffset::Between(left, _) => Some(left)
+        }
+    }
+    /// Convert the result to the leaf to the right if the offset is between
+    /// two leaves
+    pub fn right_biased(self) -> Option<N> {
+        match self {
+            TokenAtOffset::None => None,
+            TokenAtOffset::Single(node) => Some(node),
+            TokenAtOffset::Between(_, right) => Some(right)
+        }
+    }
+}
+impl<N> Iterator for TokenAtOffset<N> {
+    type Item = N;
+    fn next(&mut self) -> Option<Self::Item> {
+        match std::mem::replace(self, TokenAtOffset::None) {
+            TokenAtOffset::None => None,
+            TokenAtOffset::Single(node) => Some(node),
+            TokenAtOffset::Between(left, right) => {
+                *self = TokenAtOffset::Single(right);
+                Some(left)
+            }
+        }
+    }
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Find the leaf at the specified offset. If the offset is at the border
+    /// of two leaves, both are returned. Leaves without any text are never
+    /// returned. This descends from this node, only looking at the children
+    /// whose range contains the offset.
+    pub fn token_at_offset(&self, offset: TextUnit) -> TokenAtOffset<Self> {
+        let range = self.range();
+        if offset < range.start() || offset > range.end() || range.is_empty() {
+            return TokenAtOffset::None;
+        }
+
+        let mut current = self.clone();
+        loop {
+            let mut children = current.children_ranged()
+                .filter(|(_, range)| !range.is_empty() && range.start() <= offset && offset <= range.end())
+                .map(|(child, _)| child);
+            let left = match children.next() {
+                Some(left) => left,
+                // Only happens if the current node is a leaf
+                None => return TokenAtOffset::Single(current)
+            };
+            match children.next() {
+                None => current = left,
+                Some(right) => {
+                    // The left node ends at the offset, and the right one
+                    // starts there. So there can't be a border on either side.
+                    let left = left.token_at_offset(offset).left_biased().unwrap();
+                    let right = right.token_at_offset(offset).right_biased().unwrap();
+                    return TokenAtOffset::Between(left, right);
+                }
+            }
+        }
+    }
+    /// Find the smallest node that fully contains the specified range, or
+    /// None if this node doesn't contain it.
+    ///
+    /// This descends into a child only if it's the only child containing the
+    /// range. So an empty range on the border of two leaves returns their
+    /// closest common parent, while an empty range at the start of the first
+    /// leaf returns that leaf.
+    pub fn covering_element(&self, range: TextRange) -> Option<Self> {
+        if !range.is_subrange(&self.range()) {
+            return None;
+        }
+
+        let mut current = self.clone();
+        loop {
+            let mut children = current.children_ranged()
+                .filter(|(_, child)| range.is_subrange(child))
+                .map(|(child, _)| child);
+            match (children.next(), children.next()) {
+                (Some(child), None) => current = child,
+                _ => return Some(current)
+            }
+        }
+    }
+    /// Find the smallest node containing the specified range that matches the
+    /// predicate, starting at the covering element and going up through all
+    /// parents. See `covering_element`.
+    pub fn find_node_at_range<F>(&self, range: TextRange, pred: F) -> Option<Self>
+        where F: Fn(&Self) -> bool
+    {
+        let mut current = self.covering_element(range);
+        while let Some(node) = current {
+            if pred(&node) {
+                return Some(node);
+            }
+            current = node.parent();
+        }
+        None
+    }
+}
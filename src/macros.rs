@@ -0,0 +1,97 @@
+/// Build a tree using `TreeBuilder`, without calling it by hand. The input
+/// starts with the path to the kind type and a colon, followed by exactly one
+/// root node:
+///
+/// ```
+/// #[macro_use]
+/// extern crate rowan2;
+///
+/// #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+/// enum SyntaxKind {
+///     Group,
+///     Number,
+///     Operation
+/// }
+///
+/// fn main() {
+///     let node = tree! { SyntaxKind:
+///         Group {
+///             Number "1",
+///             Operation "+",
+///             Group {
+///                 Number "2",
+///                 Operation "*",
+///                 Number "3"
+///             }
+///         }
+///     };
+///     assert_eq!(node.to_string(), "1+2*3");
+///     assert_eq!(node.last_child().unwrap().kind(), SyntaxKind::Group);
+/// }
+/// ```
+///
+/// - `Kind { child, ... }` is a branch of `SyntaxKind::Kind` with these
+///   children, separated by commas.
+/// - `Kind text` is a leaf of `SyntaxKind::Kind`, where `text` is a literal,
+///   a variable or an expression in parentheses, and is turned into a
+///   `SmolStr` using `Into`.
+///
+/// This returns the result of `TreeBuilder::finish`, or of
+/// `TreeBuilder::finish_mut` if the kind type is preceded by `mut`, like
+/// `tree! { mut SyntaxKind: ... }`.
+#[macro_export]
+macro_rules! tree {
+    (mut $($kind:ident)::+ : $($root:tt)*) => {{
+        let mut builder = $crate::TreeBuilder::new();
+        $crate::tree!(@root builder, ($($kind)::+), $($root)*);
+        builder.finish_mut()
+    }};
+    ($($kind:ident)::+ : $($root:tt)*) => {{
+        let mut builder = $crate::TreeBuilder::new();
+        $crate::tree!(@root builder, ($($kind)::+), $($root)*);
+        builder.finish()
+    }};
+
+    (@root $builder:ident, $kind:tt, $variant:ident $body:tt $(,)*) => {
+        $crate::tree!(@node $builder, $kind, $variant $body);
+    };
+    (@root $builder:ident, $kind:tt, ) => {
+        compile_error!("expected a root node after the kind type")
+    };
+    (@root $builder:ident, $kind:tt, $($rest:tt)*) => {
+        compile_error!(concat!(
+            "expected exactly one root node like `Kind { ... }` or `Kind \"text\"`, found `",
+            stringify!($($rest)*),
+            "`"
+        ))
+    };
+
+    (@node $builder:ident, ($($kind:tt)*), $variant:ident { $($children:tt)* }) => {
+        $builder.start_internal($($kind)*::$variant);
+        $crate::tree!(@children $builder, ($($kind)*), $($children)*);
+        $builder.finish_internal();
+    };
+    (@node $builder:ident, ($($kind:tt)*), $variant:ident $text:tt) => {
+        $builder.leaf($($kind)*::$variant, ::std::convert::Into::<$crate::SmolStr>::into($text));
+    };
+
+    (@children $builder:ident, $kind:tt, ) => {};
+    (@children $builder:ident, $kind:tt, $variant:ident $body:tt) => {
+        $crate::tree!(@node $builder, $kind, $variant $body);
+    };
+    (@children $builder:ident, $kind:tt, $variant:ident $body:tt, $($rest:tt)*) => {
+        $crate::tree!(@node $builder, $kind, $variant $body);
+        $crate::tree!(@children $builder, $kind, $($rest)*);
+    };
+    (@children $builder:ident, $kind:tt, $($rest:tt)*) => {
+        compile_error!(concat!(
+            "expected a node like `Kind { ... }` or `Kind \"text\"`, found `",
+            stringify!($($rest)*),
+            "`"
+        ))
+    };
+
+    ($($rest:tt)*) => {
+        compile_error!("expected the kind type followed by a colon and the root node, like `tree! { SyntaxKind: Group { ... } }`")
+    };
+}
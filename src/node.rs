@@ -1,24 +1,173 @@
 use crate::{
-    builder::{Content, NodeId, NodeRepr},
-    lock::{Lock, RefCount}
+    builder::{Content, NodeId, NodeRepr, Slot, TreeBuilder},
+    lock::{Lock, Once, RefCount}
 };
 
 use smol_str::SmolStr;
 use std::{
     borrow::Cow,
+    error::Error,
     fmt::{self, Debug, Display},
     hash::{Hash, Hasher},
     marker::PhantomData,
-    ops::DerefMut
+    ops::{Deref, DerefMut}
 };
 use text_unit::{TextRange, TextUnit};
 
 /// The root data of the tree, such as the node arena
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct RootData<T: Copy> {
-    pub(crate) arena: Vec<Option<NodeRepr<T>>>,
-    pub(crate) ranges: Vec<(u32, Option<u32>)>
+    pub(crate) arena: Vec<Slot<T>>,
+    pub(crate) free: Vec<usize>,
+    /// The generation of new slots pushed to the arena. Compaction raises it
+    /// past all generations in use, so handles from before the compaction
+    /// never match a slot created after it.
+    pub(crate) generation: u32,
+    pub(crate) ranges: Vec<(u32, Option<u32>)>,
+    /// Lazily calculated fingerprints of all nodes, see `Node::fingerprint`
+    pub(crate) fingerprints: Once<Vec<u64>>
+}
+impl<T: Copy> RootData<T> {
+    pub(crate) fn new(arena: Vec<Slot<T>>, ranges: Vec<(u32, Option<u32>)>) -> Self {
+        Self {
+            arena,
+            free: Vec::new(),
+            generation: 0,
+            ranges,
+            fingerprints: Once::new()
+        }
+    }
+    /// Get a node, or None if it has been removed
+    pub(crate) fn get(&self, id: NodeId) -> Option<&NodeRepr<T>> {
+        self.arena.get(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.repr.as_ref())
+    }
+    /// Get a node mutably, or None if it has been removed
+    pub(crate) fn get_mut(&mut self, id: NodeId) -> Option<&mut NodeRepr<T>> {
+        self.arena.get_mut(id.index)
+            .filter(|slot| slot.generation == id.generation)
+            .and_then(|slot| slot.repr.as_mut())
+    }
+    /// Get a node, panicking if it has been removed
+    pub(crate) fn repr(&self, id: NodeId) -> &NodeRepr<T> {
+        self.get(id).expect("node has been removed from the tree")
+    }
+    /// Get a node mutably, panicking if it has been removed
+    pub(crate) fn repr_mut(&mut self, id: NodeId) -> &mut NodeRepr<T> {
+        self.get_mut(id).expect("node has been removed from the tree")
+    }
+    /// Put a node in the arena, reusing a free slot if there is one
+    fn alloc(&mut self, repr: NodeRepr<T>) -> NodeId {
+        match self.free.pop() {
+            Some(index) => {
+                let slot = &mut self.arena[index];
+                slot.repr = Some(repr);
+                NodeId { index, generation: slot.generation }
+            },
+            None => {
+                let generation = self.generation;
+                self.arena.push(Slot { generation, repr: Some(repr) });
+                NodeId { index: self.arena.len() - 1, generation }
+            }
+        }
+    }
+    /// Take a node out of the arena and mark its slot as free. The generation
+    /// is bumped so the ID of the removed node can't be used anymore.
+    fn free(&mut self, id: NodeId) -> NodeRepr<T> {
+        let slot = &mut self.arena[id.index];
+        assert_eq!(slot.generation, id.generation, "node has been removed from the tree");
+        let repr = slot.repr.take().expect("node has been removed from the tree");
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(id.index);
+        repr
+    }
+    /// Change the length of the specified node and all its parents, used to
+    /// keep lengths up to date when adding or removing nodes
+    fn update_len<F>(&mut self, mut id: Option<NodeId>, f: F)
+        where F: Fn(u32) -> u32
+    {
+        while let Some(current) = id {
+            let repr = self.repr_mut(current);
+            repr.len = f(repr.len);
+            id = repr.parent;
+        }
+    }
+    /// Insert a new node between two siblings, or as the first/last child of
+    /// the parent if either sibling is None. Returns the new node ID.
+    fn insert_between(
+        &mut self,
+        parent: Option<NodeId>,
+        prev_sibling: Option<NodeId>,
+        next_sibling: Option<NodeId>,
+        kind: T,
+        content: Option<SmolStr>
+    ) -> NodeId {
+        let len = content.as_ref().map(|text| text.len() as u32).unwrap_or(0);
+        let node = NodeRepr {
+            kind,
+
+            parent,
+            prev_sibling,
+            next_sibling,
+            content: match content {
+                None => Content::Branch(None),
+                Some(text) => Content::Leaf(text)
+            },
+            len
+        };
+        let id = self.alloc(node);
+
+        if let Some(prev_sibling) = prev_sibling {
+            self.repr_mut(prev_sibling).next_sibling = Some(id);
+        } else if let Some(parent) = parent {
+            *self.repr_mut(parent).content.expect_branch() = Some(id);
+        }
+        if let Some(next_sibling) = next_sibling {
+            self.repr_mut(next_sibling).prev_sibling = Some(id);
+        }
+        self.update_len(parent, |old| old + len);
+
+        id
+    }
+    /// Get the range of a node. If there are no stored ranges, it's calculated
+    /// by adding up the lengths of all previous siblings of the node and its
+    /// parents.
+    pub(crate) fn range(&self, id: NodeId) -> TextRange {
+        if !self.ranges.is_empty() {
+            let range = self.ranges[id.index];
+            return TextRange::from_to(TextUnit::from(range.0), TextUnit::from(range.1.unwrap()));
+        }
+        let get = |id: NodeId| self.repr(id);
+        let mut start = 0;
+        let mut current = Some(id);
+        while let Some(node) = current {
+            let mut prev = get(node).prev_sibling;
+            while let Some(sibling) = prev {
+                start += get(sibling).len;
+                prev = get(sibling).prev_sibling;
+            }
+            current = get(node).parent;
+        }
+        TextRange::offset_len(TextUnit::from(start), TextUnit::from(get(id).len))
+    }
+}
+
+/// The error returned by fallible accessors of a node that has been removed
+/// from its tree
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NodeRemoved;
+impl Display for NodeRemoved {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "node has been removed from the tree")
+    }
 }
+impl Error for NodeRemoved {}
+
+/// An opaque identifier of a tree, which is unique for as long as the tree is
+/// alive. See `Node::root_id`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RootId(usize);
 
 /// An internal trait for allowing multiple ways to access the tree root.
 /// Don't implement this yourself, instead use for example `OwnedRoot` or
@@ -28,13 +177,15 @@ pub trait TreeRoot<T: Copy>: Clone {
     fn with_data<F, V>(&self, f: F) -> V
         where F: FnOnce(&RootData<T>) -> V;
     fn borrow_data(&self) -> Option<&RootData<T>>;
-    fn borrowed(&self) -> RefRoot<T, Self::Borrowed>;
+    fn borrowed(&self) -> RefRoot<'_, T, Self::Borrowed>;
+    fn root_id(&self) -> RootId;
 }
 
 /// A tree root that allows you to mutate inner data by using interior
-/// mutability. Very similar to `OwnedRoot`.
+/// mutability. Very similar to `OwnedRoot`. The data is copy-on-write, so a
+/// tree thawed from an `OwnedRoot` shares its arena until the first change.
 #[derive(Clone, Debug)]
-pub struct MutableRoot<T: Copy>(RefCount<Lock<RootData<T>>>);
+pub struct MutableRoot<T: Copy>(RefCount<Lock<RefCount<RootData<T>>>>);
 impl<T: Copy> TreeRoot<T> for MutableRoot<T> {
     type Borrowed = Self;
     fn with_data<F, V>(&self, f: F) -> V
@@ -45,10 +196,13 @@ impl<T: Copy> TreeRoot<T> for MutableRoot<T> {
     fn borrow_data(&self) -> Option<&RootData<T>> {
         None
     }
-    fn borrowed(&self) -> RefRoot<T, Self::Borrowed> {
+    fn root_id(&self) -> RootId {
+        RootId(RefCount::as_ptr(&self.0) as usize)
+    }
+    fn borrowed(&self) -> RefRoot<'_, T, Self::Borrowed> {
         RefRoot {
             inner: self,
-            _marker: PhantomData::default()
+            _marker: PhantomData
         }
     }
 }
@@ -67,10 +221,13 @@ impl<T: Copy> TreeRoot<T> for OwnedRoot<T> {
     fn borrow_data(&self) -> Option<&RootData<T>> {
         Some(&self.0)
     }
-    fn borrowed(&self) -> RefRoot<T, Self::Borrowed> {
+    fn root_id(&self) -> RootId {
+        RootId(RefCount::as_ptr(&self.0) as usize)
+    }
+    fn borrowed(&self) -> RefRoot<'_, T, Self::Borrowed> {
         RefRoot {
             inner: self,
-            _marker: PhantomData::default()
+            _marker: PhantomData
         }
     }
 }
@@ -80,7 +237,7 @@ impl<T: Copy> TreeRoot<T> for OwnedRoot<T> {
 /// try to store nodes of this type.
 #[derive(Clone, Debug)]
 pub struct RefRoot<'a, T: Copy + 'a, R: TreeRoot<T> + 'a> {
-    inner: &'a R,
+    pub(crate) inner: &'a R,
     _marker: PhantomData<T>
 }
 impl<'a, T: Copy, R: TreeRoot<T>> Copy for RefRoot<'a, T, R> {}
@@ -94,24 +251,53 @@ impl<'a, T: Copy, R: TreeRoot<T>> TreeRoot<T> for RefRoot<'a, T, R> {
     fn borrow_data(&self) -> Option<&RootData<T>> {
         self.inner.borrow_data()
     }
-    fn borrowed(&self) -> RefRoot<T, Self::Borrowed> {
+    fn root_id(&self) -> RootId {
+        self.inner.root_id()
+    }
+    fn borrowed(&self) -> RefRoot<'_, T, Self::Borrowed> {
         *self
     }
 }
 
-/// The node type
-#[derive(Clone, Eq)]
+/// A write guard for the data of a `MutableRoot`, which makes sure the data
+/// isn't shared before handing out mutable references to it
+struct DataMut<G>(G);
+impl<T: Copy, G: Deref<Target = RefCount<RootData<T>>>> Deref for DataMut<G> {
+    type Target = RootData<T>;
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+impl<T: Copy, G: DerefMut<Target = RefCount<RootData<T>>>> DerefMut for DataMut<G> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        RefCount::make_mut(&mut self.0)
+    }
+}
+
+/// The node type. Two nodes are equal if they are the same node in the same
+/// tree, regardless of how the tree root is accessed.
+#[derive(Clone)]
 pub struct Node<T: Copy, R: TreeRoot<T>> {
-    root: R,
-    node: NodeId,
-    _marker: PhantomData<T>
+    pub(crate) root: R,
+    pub(crate) node: NodeId,
+    pub(crate) _marker: PhantomData<T>
 }
 impl<T: Copy> Node<T, OwnedRoot<T>> {
     pub(crate) fn new_root(data: RootData<T>, node: NodeId) -> Self {
         Node {
             root: OwnedRoot(RefCount::new(data)),
             node,
-            _marker: PhantomData::default()
+            _marker: PhantomData
+        }
+    }
+    /// Turn this tree into a mutable one. This is cheap: the arena is shared
+    /// with this tree and only copied once the mutable tree is first changed.
+    /// The returned node is the equivalent of this node in the new tree.
+    pub fn thaw(&self) -> Node<T, MutableRoot<T>> {
+        Node {
+            root: MutableRoot(RefCount::new(Lock::new(self.root.0.clone()))),
+            node: self.node,
+            _marker: PhantomData
         }
     }
 }
@@ -122,14 +308,14 @@ impl<'a, T: Copy> Node<T, RefRoot<'a, T, OwnedRoot<T>>> {
         Node {
             root: self.root.inner.clone(),
             node: self.node,
-            _marker: PhantomData::default()
+            _marker: PhantomData
         }
     }
     /// Convert this borrowed node into its inner leaf text with the same
     /// lifetime.
     pub fn leaf_text(self) -> Option<&'a SmolStr> {
         let data = &self.root.inner.0;
-        let repr = data.arena[self.node.0].as_ref().unwrap();
+        let repr = data.repr(self.node);
         match repr.content {
             Content::Branch(_) => None,
             Content::Leaf(ref s) => Some(s)
@@ -139,123 +325,279 @@ impl<'a, T: Copy> Node<T, RefRoot<'a, T, OwnedRoot<T>>> {
 impl<T: Copy> Node<T, MutableRoot<T>> {
     pub(crate) fn new_root_mut(data: RootData<T>, node: NodeId) -> Self {
         Node {
-            root: MutableRoot(RefCount::new(Lock::new(data))),
+            root: MutableRoot(RefCount::new(Lock::new(RefCount::new(data)))),
             node,
-            _marker: PhantomData::default()
+            _marker: PhantomData
         }
     }
-    fn data_mut<'a>(&'a self) -> impl DerefMut<Target = RootData<T>> + 'a {
-        self.root.0.write()
+    pub(crate) fn data_mut(&self) -> impl DerefMut<Target = RootData<T>> + '_ {
+        let mut data = DataMut(self.root.0.write());
+        // Stored ranges aren't kept up to date, mutable trees calculate them
+        // from lengths instead. So drop them before the first change. If the
+        // data is shared with an immutable tree, this is what copies it.
+        if !data.ranges.is_empty() {
+            data.ranges = Vec::new();
+        }
+        if data.fingerprints.get().is_some() {
+            data.fingerprints = Once::new();
+        }
+        data
     }
-    /// Remove this node from the tree. This frees all children.
+    /// Remove this node from the tree. This frees all children, recursively.
     pub fn remove(self) {
         let mut data = self.data_mut();
-        let repr = data.arena[self.node.0].take().unwrap();
+        let repr = data.free(self.node);
+        data.update_len(repr.parent, |len| len - repr.len);
 
-        // Free all children
+        // Free all children. Instead of recursing, remember the first child
+        // of each branch and continue there once all siblings are freed.
+        let mut branches = Vec::new();
         let mut next = match repr.content {
             Content::Branch(child) => child,
             Content::Leaf(_) => None
         };
         while let Some(current) = next {
-            next = data.arena[current.0].take().unwrap().next_sibling;
+            let child = data.free(current);
+            if let Content::Branch(Some(first)) = child.content {
+                branches.push(first);
+            }
+            next = child.next_sibling.or_else(|| branches.pop());
         }
 
+        if let Some(next_sibling) = repr.next_sibling {
+            data.repr_mut(next_sibling).prev_sibling = repr.prev_sibling;
+        }
         if let Some(prev_sibling) = repr.prev_sibling {
             // Remove the node by linking the previous node directly to the next
-            data.arena[prev_sibling.0].as_mut().unwrap().next_sibling = repr.next_sibling;
+            data.repr_mut(prev_sibling).next_sibling = repr.next_sibling;
         } else if let Some(parent) = repr.parent {
             // Remove the node by linking the parent directly to the next
-            *data.arena[parent.0].as_mut().unwrap().content.expect_branch() = repr.next_sibling;
+            *data.repr_mut(parent).content.expect_branch() = repr.next_sibling;
         }
     }
     /// Insert a new node right before this node
     pub fn insert_before(&self, kind: T, content: Option<SmolStr>) -> Self {
         let mut data = self.data_mut();
-        let node = {
-            let repr = data.arena[self.node.0].as_ref().unwrap();
-            NodeRepr {
-                kind,
-
-                parent: repr.parent,
-                prev_sibling: repr.prev_sibling,
-                next_sibling: Some(self.node),
-                content: match content {
-                    None => Content::Branch(None),
-                    Some(text) => Content::Leaf(text)
-                }
-            }
+        let (parent, prev_sibling) = {
+            let repr = data.repr(self.node);
+            (repr.parent, repr.prev_sibling)
         };
-        let id = NodeId(data.arena.len());
-        data.arena.push(Some(node));
-
-        {
-            if let Some(prev_sibling) = data.arena[self.node.0].as_ref().unwrap().prev_sibling {
-                data.arena[prev_sibling.0].as_mut().unwrap().next_sibling = Some(id);
-            }
-            if let Some(parent) = data.arena[self.node.0].as_ref().unwrap().parent {
-                let parent = data.arena[parent.0].as_mut().unwrap();
-                if parent.content == Content::Branch(Some(self.node)) {
-                    parent.content = Content::Branch(Some(id));
-                }
-            }
-        }
-        data.arena[self.node.0].as_mut().unwrap().prev_sibling = Some(id);
-
+        let id = data.insert_between(parent, prev_sibling, Some(self.node), kind, content);
         self.with_node(id)
     }
     /// Insert a new node directly after this node
     pub fn insert_after(&self, kind: T, content: Option<SmolStr>) -> Self {
         let mut data = self.data_mut();
-        let node = {
-            let repr = data.arena[self.node.0].as_ref().unwrap();
-            NodeRepr {
-                kind,
+        let (parent, next_sibling) = {
+            let repr = data.repr(self.node);
+            (repr.parent, repr.next_sibling)
+        };
+        let id = data.insert_between(parent, Some(self.node), next_sibling, kind, content);
+        self.with_node(id)
+    }
+    /// Insert a new node as the child at the specified index of this branch,
+    /// so that it's preceded by `index` other children.
+    ///
+    /// # Panics
+    /// This function panics if this node is a leaf or if the index is greater
+    /// than the number of children.
+    pub fn insert_child(&self, index: usize, kind: T, content: Option<SmolStr>) -> Self {
+        let mut data = self.data_mut();
+        let mut prev_sibling = None;
+        let mut next_sibling = *data.repr_mut(self.node).content.expect_branch();
+        for _ in 0..index {
+            let current = next_sibling.expect("child index out of bounds");
+            prev_sibling = Some(current);
+            next_sibling = data.repr(current).next_sibling;
+        }
+        let id = data.insert_between(Some(self.node), prev_sibling, next_sibling, kind, content);
+        self.with_node(id)
+    }
+    /// Insert a new node as the first child of this branch
+    ///
+    /// # Panics
+    /// This function panics if this node is a leaf.
+    pub fn prepend_child(&self, kind: T, content: Option<SmolStr>) -> Self {
+        self.insert_child(0, kind, content)
+    }
+    /// Insert a new node as the last child of this branch
+    ///
+    /// # Panics
+    /// This function panics if this node is a leaf.
+    pub fn append_child(&self, kind: T, content: Option<SmolStr>) -> Self {
+        let mut data = self.data_mut();
+        let mut prev_sibling = None;
+        let mut next = *data.repr_mut(self.node).content.expect_branch();
+        while let Some(current) = next {
+            prev_sibling = Some(current);
+            next = data.repr(current).next_sibling;
+        }
+        let id = data.insert_between(Some(self.node), prev_sibling, None, kind, content);
+        self.with_node(id)
+    }
+    /// Turn the whole tree back into an immutable one. This re-calculates all
+    /// ranges from the leaf text and leaves out any slots freed by `remove`.
+    /// The returned node is the equivalent of this node in the new tree,
+    /// which is always a different tree from the one this was thawed from.
+    ///
+    /// # Panics
+    /// This function panics if this node has been removed, or if the tree
+    /// has more than one top-level node, for example after `insert_after` was
+    /// called on the root.
+    pub fn freeze(&self) -> Node<T, OwnedRoot<T>> {
+        let data = self.root.0.read();
+        if !data.ranges.is_empty() {
+            // Thawed and never changed, the ranges are still correct. Copy the
+            // data anyway, so the frozen tree doesn't share its root ID with
+            // the tree it was thawed from.
+            return Node {
+                root: OwnedRoot(RefCount::new(RootData::clone(&data))),
+                node: self.node,
+                _marker: PhantomData
+            };
+        }
+        let get = |id: NodeId| data.repr(id);
 
-                parent: repr.parent,
-                prev_sibling: Some(self.node),
-                next_sibling: repr.next_sibling,
-                content: match content {
-                    None => Content::Branch(None),
-                    Some(text) => Content::Leaf(text)
+        let mut root = self.node;
+        while let Some(parent) = get(root).parent {
+            root = parent;
+        }
+        assert!(
+            get(root).prev_sibling.is_none() && get(root).next_sibling.is_none(),
+            "can't freeze more than one root node"
+        );
+
+        // Feed the tree to a builder in document order. The builder assigns
+        // ids in insertion order, so counting nodes gives us the new id.
+        let mut builder = TreeBuilder::new();
+        let mut count = 0;
+        let mut this = None;
+        let mut current = root;
+        'outer: loop {
+            let repr = get(current);
+            if current == self.node {
+                this = Some(NodeId::new(count));
+            }
+            count += 1;
+
+            match repr.content {
+                Content::Leaf(ref text) => builder.leaf(repr.kind, text.clone()),
+                Content::Branch(child) => {
+                    builder.start_internal(repr.kind);
+                    if let Some(child) = child {
+                        current = child;
+                        continue;
+                    }
+                    builder.finish_internal();
                 }
             }
-        };
-        let id = NodeId(data.arena.len());
-        data.arena.push(Some(node));
 
-        {
-            if let Some(next_sibling) = data.arena[self.node.0].as_ref().unwrap().next_sibling {
-                data.arena[next_sibling.0].as_mut().unwrap().prev_sibling = Some(id);
+            // Go to the next node, finishing all branches we walk out of
+            loop {
+                if current == root {
+                    break 'outer;
+                }
+                let repr = get(current);
+                if let Some(next) = repr.next_sibling {
+                    current = next;
+                    continue 'outer;
+                }
+                current = repr.parent.unwrap();
+                builder.finish_internal();
             }
         }
-        data.arena[self.node.0].as_mut().unwrap().next_sibling = Some(id);
 
-        self.with_node(id)
+        let tree = builder.finish();
+        Node {
+            root: tree.root,
+            node: this.unwrap(),
+            _marker: PhantomData
+        }
     }
 }
 impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
     /// Borrow this node, getting a cheap node type that implements Copy. See
     /// RefRoot for details.
-    pub fn borrowed(&self) -> Node<T, RefRoot<T, R::Borrowed>> {
+    pub fn borrowed(&self) -> Node<T, RefRoot<'_, T, R::Borrowed>> {
         Node {
             root: self.root.borrowed(),
             node: self.node,
-            _marker: PhantomData::default()
+            _marker: PhantomData
         }
     }
     fn repr<F, V>(&self, f: F) -> V
         where F: FnOnce(&NodeRepr<T>) -> V
     {
         self.root.with_data(move |data| {
-            f(&data.arena[self.node.0].as_ref().unwrap())
+            f(data.repr(self.node))
         })
     }
-    fn with_node(&self, node: NodeId) -> Self {
+    fn try_repr<F, V>(&self, f: F) -> Result<V, NodeRemoved>
+        where F: FnOnce(&NodeRepr<T>) -> V
+    {
+        self.root.with_data(move |data| {
+            data.get(self.node).map(f).ok_or(NodeRemoved)
+        })
+    }
+    pub(crate) fn with_node(&self, node: NodeId) -> Self {
         Node {
             root: self.root.clone(),
             node,
-            _marker: PhantomData::default()
+            _marker: PhantomData
+        }
+    }
+    /// Get the identifier of the tree this node belongs to. A borrowed node
+    /// has the same root ID as the node it was borrowed from, but a thawed or
+    /// frozen tree is a different tree.
+    pub fn root_id(&self) -> RootId {
+        self.root.root_id()
+    }
+    /// Return true if both nodes belong to the same tree
+    pub fn same_tree<R2: TreeRoot<T>>(&self, other: &Node<T, R2>) -> bool {
+        self.root_id() == other.root_id()
+    }
+    /// Compare only the position of the nodes in their arena, ignoring which
+    /// tree they belong to. Unlike `==`, this may consider nodes of two
+    /// different trees equal.
+    pub fn id_eq<R2: TreeRoot<T>>(&self, other: &Node<T, R2>) -> bool {
+        self.node == other.node
+    }
+    /// Return true if this node is still part of the tree. Nodes of mutable
+    /// trees die when they or any of their parents are removed, after which
+    /// all accessors except the fallible `try_*` ones panic.
+    pub fn is_alive(&self) -> bool {
+        self.root.with_data(|data| data.get(self.node).is_some())
+    }
+    /// Get the parent node, or an error if this node has been removed
+    pub fn try_parent(&self) -> Result<Option<Self>, NodeRemoved> {
+        self.try_repr(|repr| repr.parent).map(|node| node.map(|node| self.with_node(node)))
+    }
+    /// Get the next sibling, or an error if this node has been removed
+    pub fn try_next_sibling(&self) -> Result<Option<Self>, NodeRemoved> {
+        self.try_repr(|repr| repr.next_sibling).map(|node| node.map(|node| self.with_node(node)))
+    }
+    /// Get the previous sibling, or an error if this node has been removed
+    pub fn try_prev_sibling(&self) -> Result<Option<Self>, NodeRemoved> {
+        self.try_repr(|repr| repr.prev_sibling).map(|node| node.map(|node| self.with_node(node)))
+    }
+    /// Get the first child, or an error if this node has been removed
+    pub fn try_first_child(&self) -> Result<Option<Self>, NodeRemoved> {
+        self.try_repr(|repr| match repr.content {
+            Content::Branch(child) => child.map(|node| self.with_node(node)),
+            Content::Leaf(_) => None
+        })
+    }
+    /// Get the node kind, or an error if this node has been removed
+    pub fn try_kind(&self) -> Result<T, NodeRemoved> {
+        self.try_repr(|repr| repr.kind)
+    }
+    /// Get the leaf text, or an error if this node has been removed. Like
+    /// `leaf_text_cow`, this clones the text if the tree root is mutable.
+    pub fn try_leaf_text_cow(&self) -> Result<Option<Cow<'_, SmolStr>>, NodeRemoved> {
+        if self.is_alive() {
+            Ok(self.leaf_text_cow())
+        } else {
+            Err(NodeRemoved)
         }
     }
     /// Get the parent node
@@ -283,10 +625,26 @@ impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
             next: self.first_child()
         }
     }
+    /// Get an iterator over all children together with their ranges. Unlike
+    /// calling `range` on each child, this doesn't start over from the
+    /// beginning of the tree for every child on mutable trees.
+    pub(crate) fn children_ranged(&self) -> impl Iterator<Item = (Self, TextRange)> {
+        let stored = self.root.with_data(|data| !data.ranges.is_empty());
+        let mut offset = self.range().start();
+        self.children().map(move |child| {
+            let range = if stored {
+                child.range()
+            } else {
+                TextRange::offset_len(offset, TextUnit::from(child.repr(|repr| repr.len)))
+            };
+            offset = range.end();
+            (child, range)
+        })
+    }
     /// Get the leaf text. If the tree root is mutable this will clone the text.
-    pub fn leaf_text_cow(&self) -> Option<Cow<SmolStr>> {
+    pub fn leaf_text_cow(&self) -> Option<Cow<'_, SmolStr>> {
         if let Some(data) = self.root.borrow_data() {
-            let repr = data.arena[self.node.0].as_ref().unwrap();
+            let repr = data.repr(self.node);
             match repr.content {
                 Content::Branch(_) => None,
                 Content::Leaf(ref s) => Some(Cow::Borrowed(s))
@@ -298,23 +656,21 @@ impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
             })
         }
     }
-    /// Try getting the range. This will always succeed on immutable tree
-    /// roots, but always fail on mutable onces as they don't store range data.
+    /// Try getting the range. Immutable tree roots store all ranges, while
+    /// mutable ones calculate it from the length of each node, which is kept
+    /// up to date on every change. This only fails if the node was removed.
     pub fn try_range(&self) -> Option<TextRange> {
         self.root.with_data(|data| {
-            if data.ranges.is_empty() {
-                return None;
-            }
-            let range = data.ranges[self.node.0];
-            Some(TextRange::from_to(TextUnit::from(range.0), TextUnit::from(range.1.unwrap())))
+            data.get(self.node)?;
+            Some(data.range(self.node))
         })
     }
     /// Get the text range
     ///
     /// # Panics
-    /// This function panics if the tree root is mutable, because those don't store range data
+    /// This function panics if the node has been removed
     pub fn range(&self) -> TextRange {
-        self.try_range().expect("node is mutable and doesn't have a range")
+        self.try_range().expect("node has been removed from the tree")
     }
     /// Get the node kind
     pub fn kind(&self) -> T {
@@ -324,16 +680,21 @@ impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
     pub fn walk(&self) -> NodeWalker<T, R> {
         NodeWalker {
             next: Some(WalkEvent::Enter(self.clone())),
-            nested: 0
+            nested: 0,
+            entered: None,
+            reverse: false,
+            range: None,
+            next_range: None,
+            ranges: Vec::new(),
+            max_depth: None
         }
     }
 }
 impl<T: Copy + Debug, R: TreeRoot<T>> Debug for Node<T, R> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if let Some(range) = self.try_range() {
-            write!(f, "{:?}@{:?}", self.kind(), range)
-        } else {
-            write!(f, "{:?}@MUT", self.kind())
+        match (self.try_kind(), self.try_range()) {
+            (Ok(kind), Some(range)) => write!(f, "{:?}@{:?}", kind, range),
+            _ => write!(f, "REMOVED")
         }
     }
 }
@@ -351,11 +712,13 @@ impl<T: Copy, R: TreeRoot<T>> Display for Node<T, R> {
 }
 impl<T: Copy, R: TreeRoot<T>> PartialEq for Node<T, R> {
     fn eq(&self, other: &Self) -> bool {
-        self.node == other.node
+        self.node == other.node && self.root_id() == other.root_id()
     }
 }
+impl<T: Copy, R: TreeRoot<T>> Eq for Node<T, R> {}
 impl<T: Copy, R: TreeRoot<T>> Hash for Node<T, R> {
     fn hash<H: Hasher>(&self, state: &mut H) {
+        self.root_id().hash(state);
         self.node.hash(state);
     }
 }
@@ -392,20 +755,132 @@ pub struct NodeWalker<T: Copy, R: TreeRoot<T>> {
     next: Option<WalkEvent<T, R>>,
     /// how many levels deep we are. this is used to stop once we reach the
     /// same parent we started at.
-    nested: usize
+    nested: usize,
+    /// the node of the last enter event, until its children are walked. this
+    /// is used to skip them.
+    entered: Option<Node<T, R>>,
+    /// whether to walk children from last to first
+    reverse: bool,
+    /// the range nodes need to intersect to be walked
+    range: Option<TextRange>,
+    /// the range of the node of the next event, if walking in a range. ranges
+    /// are calculated from the lengths of siblings as we go, because getting
+    /// the range of each node is slow on mutable trees.
+    next_range: Option<TextRange>,
+    /// the ranges of all nodes we're inside of, if walking in a range
+    ranges: Vec<TextRange>,
+    /// how many levels deep nodes can be to be walked
+    max_depth: Option<usize>
+}
+impl<T: Copy, R: TreeRoot<T>> NodeWalker<T, R> {
+    /// Walk children from last to first instead, so that the whole tree is
+    /// walked from the end. This should be called before iterating.
+    pub fn reversed(mut self) -> Self {
+        self.reverse = true;
+        self
+    }
+    /// Only walk nodes that intersect with the specified range, including
+    /// those only touching its borders. The node the walk started at is
+    /// always walked. This should be called before iterating.
+    pub fn in_range(mut self, range: TextRange) -> Self {
+        self.range = Some(range);
+        if let Some(WalkEvent::Enter(ref node)) = self.next {
+            self.next_range = Some(node.range());
+        }
+        self
+    }
+    /// Only walk nodes up to the specified amount of levels below the node
+    /// the walk started at, which is at depth 0. This should be called before
+    /// iterating.
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+    /// Skip all children of the node that was just entered, so that the next
+    /// event is leaving that node. Does nothing if the last event was `Leave`.
+    pub fn skip_subtree(&mut self) {
+        if let Some(node) = self.entered.take() {
+            if let Some(WalkEvent::Enter(_)) = self.next {
+                // The children were about to be entered, so the range of the
+                // node was already saved
+                self.next_range = self.ranges.pop();
+            }
+            self.next = Some(WalkEvent::Leave(node));
+        }
+    }
+    /// Get the next sibling in the walking direction
+    fn step(&self, node: &Node<T, R>) -> Option<Node<T, R>> {
+        if self.reverse {
+            node.prev_sibling()
+        } else {
+            node.next_sibling()
+        }
+    }
+    /// Find the first node in the walking direction, starting at the specified
+    /// one, which is in range. `edge` is the range of the node before it in
+    /// the walking direction, or an empty range at the border of the parent if
+    /// there is none. Returns the node together with its range, if walking in
+    /// a range.
+    fn in_range_from(&self, mut next: Option<Node<T, R>>, edge: Option<TextRange>)
+        -> Option<(Node<T, R>, Option<TextRange>)>
+    {
+        let (range, mut edge) = match (self.range, edge) {
+            (Some(range), Some(edge)) => (range, edge),
+            _ => return next.map(|node| (node, None))
+        };
+        while let Some(node) = next {
+            let len = TextUnit::from(node.repr(|repr| repr.len));
+            let node_range = if self.reverse {
+                TextRange::from_to(edge.start() - len, edge.start())
+            } else {
+                TextRange::offset_len(edge.end(), len)
+            };
+            edge = node_range;
+            if node_range.intersection(&range).is_some() {
+                return Some((node, Some(node_range)));
+            }
+            // Siblings are in order, so everything after this is out of range
+            if (self.reverse && node_range.end() < range.start())
+                    || (!self.reverse && node_range.start() > range.end()) {
+                return None;
+            }
+            next = self.step(&node);
+        }
+        None
+    }
 }
 impl<T: Copy, R: TreeRoot<T>> Iterator for NodeWalker<T, R> {
     type Item = (usize, WalkEvent<T, R>);
     fn next(&mut self) -> Option<Self::Item> {
         let next = self.next.take();
+        let next_range = self.next_range.take();
+        self.entered = None;
         let (nested, new) = match next {
             None => (0, None),
             Some(WalkEvent::Enter(ref node)) => {
                 let old_nested = self.nested;
                 self.nested += 1;
-                (old_nested, Some(match node.first_child() {
-                    Some(child) => WalkEvent::Enter(child),
-                    None => WalkEvent::Leave(node.clone())
+                self.entered = Some(node.clone());
+
+                let child = if self.max_depth.is_some_and(|max| self.nested > max) {
+                    None
+                } else if self.reverse {
+                    let edge = next_range.map(|range| TextRange::offset_len(range.end(), 0.into()));
+                    self.in_range_from(node.last_child(), edge)
+                } else {
+                    let edge = next_range.map(|range| TextRange::offset_len(range.start(), 0.into()));
+                    self.in_range_from(node.first_child(), edge)
+                };
+                (old_nested, Some(match child {
+                    Some((child, child_range)) => {
+                        self.ranges.extend(next_range);
+                        self.next_range = child_range;
+                        WalkEvent::Enter(child)
+                    },
+                    None => {
+                        self.next_range = next_range;
+                        WalkEvent::Leave(node.clone())
+                    }
                 }))
             },
             Some(WalkEvent::Leave(ref node)) => {
@@ -413,9 +888,15 @@ impl<T: Copy, R: TreeRoot<T>> Iterator for NodeWalker<T, R> {
                 (self.nested, if self.nested == 0 {
                     None
                 } else {
-                    match node.next_sibling() {
-                        Some(next) => Some(WalkEvent::Enter(next)),
-                        None => node.parent().map(WalkEvent::Leave)
+                    match self.in_range_from(self.step(node), next_range) {
+                        Some((next, range)) => {
+                            self.next_range = range;
+                            Some(WalkEvent::Enter(next))
+                        },
+                        None => {
+                            self.next_range = self.ranges.pop();
+                            node.parent().map(WalkEvent::Leave)
+                        }
                     }
                 })
             }
@@ -0,0 +1,646 @@
+use crate::{
+    lex,
+    node::{Node, NodeWalker, TreeRoot, WalkEvent}
+};
+
+use smol_str::SmolStr;
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display}
+};
+use text_unit::TextRange;
+
+/// What went wrong when compiling a query, see `QueryError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum QueryErrorKind {
+    /// The query ended in the middle of a pattern
+    UnexpectedEnd,
+    /// A character that isn't allowed at this position
+    UnexpectedChar,
+    /// The resolver didn't recognize a kind name
+    UnknownKind,
+    /// A predicate other than `#eq?`, `#not-eq?`, `#any-of?` and
+    /// `#not-any-of?`
+    UnknownPredicate,
+    /// A predicate with the wrong amount or type of arguments
+    InvalidPredicate,
+    /// A predicate refers to a capture that isn't defined before it
+    UnknownCapture
+}
+
+/// An error returned by `Query::new` when the query is malformed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct QueryError {
+    /// The byte offset in the query where the error was found
+    pub offset: usize,
+    /// What went wrong
+    pub kind: QueryErrorKind
+}
+impl Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            QueryErrorKind::UnexpectedEnd => "unexpected end of query",
+            QueryErrorKind::UnexpectedChar => "unexpected character",
+            QueryErrorKind::UnknownKind => "unknown kind",
+            QueryErrorKind::UnknownPredicate => "unknown predicate",
+            QueryErrorKind::InvalidPredicate => "invalid predicate arguments",
+            QueryErrorKind::UnknownCapture => "unknown capture"
+        };
+        write!(f, "{} at offset {}", reason, self.offset)
+    }
+}
+impl Error for QueryError {}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Quantifier {
+    One,
+    ZeroOrOne,
+    ZeroOrMore,
+    OneOrMore
+}
+impl Quantifier {
+    /// The minimum and maximum amount of times the pattern may match
+    fn bounds(self) -> (usize, usize) {
+        match self {
+            Quantifier::One => (1, 1),
+            Quantifier::ZeroOrOne => (0, 1),
+            Quantifier::ZeroOrMore => (0, usize::MAX),
+            Quantifier::OneOrMore => (1, usize::MAX)
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum Matcher<T> {
+    /// A node of the kind, or of any kind if None, with children matching
+    /// the child patterns in order
+    Node {
+        kind: Option<T>,
+        children: Vec<Child<T>>,
+        anchor_end: bool
+    },
+    /// A leaf with exactly this text
+    Text(SmolStr)
+}
+#[derive(Clone, Debug)]
+struct Pattern<T> {
+    matcher: Matcher<T>,
+    captures: Vec<usize>
+}
+#[derive(Clone, Debug)]
+struct Child<T> {
+    pattern: Pattern<T>,
+    quantifier: Quantifier,
+    /// whether the first match has to come directly after the previous child
+    /// pattern, or be the first child
+    anchored: bool
+}
+
+#[derive(Clone, Debug)]
+enum Arg {
+    Capture(usize),
+    Text(SmolStr)
+}
+#[derive(Clone, Debug)]
+enum Predicate {
+    Eq {
+        capture: usize,
+        other: Arg,
+        negate: bool
+    },
+    AnyOf {
+        capture: usize,
+        values: Vec<SmolStr>,
+        negate: bool
+    }
+}
+
+type Captures<T, R> = Vec<(usize, Node<T, R>)>;
+
+impl Predicate {
+    /// Check the predicate against the captures. If a capture has multiple
+    /// nodes, the predicate has to hold for all of them, so captures without
+    /// any nodes yet always pass.
+    fn check<T: Copy, R: TreeRoot<T>>(&self, captures: &Captures<T, R>) -> bool {
+        let texts = |capture: usize| {
+            captures.iter()
+                .filter(move |(index, _)| *index == capture)
+                .map(|(_, node)| node.to_string())
+        };
+        match *self {
+            Predicate::Eq { capture, ref other, negate } => texts(capture).all(|text| match *other {
+                Arg::Capture(other) => texts(other).all(|other| (text == other) != negate),
+                Arg::Text(ref other) => (text == other.as_str()) != negate
+            }),
+            Predicate::AnyOf { capture, ref values, negate } => texts(capture).all(|text| {
+                values.iter().any(|value| text == value.as_str()) != negate
+            })
+        }
+    }
+}
+
+/// A position in the backtracking search of `match_pattern`
+struct State<T: Copy, R: TreeRoot<T>> {
+    /// the index of the child pattern to match
+    index: usize,
+    /// how many times that child pattern has matched
+    reps: usize,
+    /// the sibling after the last match
+    next: Option<Node<T, R>>,
+    /// the next sibling to try matching the child pattern one more time
+    candidate: Option<Node<T, R>>,
+    /// whether moving on to the next child pattern has been tried
+    advanced: bool,
+    /// the amount of captures when this state was reached
+    captures: usize
+}
+
+/// Try to match the pattern against the node, adding to the captures on
+/// success. Children are matched by backtracking over which siblings each
+/// child pattern matches, but each child node only matches in the first way
+/// found. Whenever all child patterns match, the predicates are checked to
+/// decide whether to keep looking.
+fn match_pattern<T, R>(pattern: &Pattern<T>, node: &Node<T, R>, predicates: &[Predicate], captures: &mut Captures<T, R>) -> bool
+    where T: Copy + PartialEq,
+          R: TreeRoot<T>
+{
+    let len = captures.len();
+    let (children, anchor_end) = match pattern.matcher {
+        Matcher::Text(ref text) => {
+            match node.leaf_text_cow() {
+                Some(ref leaf) if **leaf == *text => (),
+                _ => return false
+            }
+            (&[][..], false)
+        },
+        Matcher::Node { kind, ref children, anchor_end } => {
+            if !kind.is_none_or(|kind| kind == node.kind()) {
+                return false;
+            }
+            (&children[..], anchor_end)
+        }
+    };
+    captures.extend(pattern.captures.iter().map(|&index| (index, node.clone())));
+
+    let first = node.first_child();
+    let mut stack = vec![State {
+        index: 0,
+        reps: 0,
+        next: first.clone(),
+        candidate: first,
+        advanced: false,
+        captures: captures.len()
+    }];
+    while let Some(state) = stack.last_mut() {
+        captures.truncate(state.captures);
+        let child = match children.get(state.index) {
+            Some(child) => child,
+            None => {
+                let done = (!anchor_end || state.next.is_none())
+                    && predicates.iter().all(|predicate| predicate.check(captures));
+                if done {
+                    return true;
+                }
+                stack.pop();
+                continue;
+            }
+        };
+        let (min, max) = child.quantifier.bounds();
+
+        // Try matching one more time first, to be greedy
+        if let Some(node) = state.candidate.take() {
+            let after = node.next_sibling();
+            // Only the first match may skip siblings, repetitions have to
+            // follow each other directly
+            if !child.anchored && state.reps == 0 {
+                state.candidate = after.clone();
+            }
+            if match_pattern(&child.pattern, &node, predicates, captures) {
+                let reps = state.reps + 1;
+                let index = state.index;
+                stack.push(State {
+                    index,
+                    reps,
+                    next: after.clone(),
+                    candidate: if reps < max { after } else { None },
+                    advanced: false,
+                    captures: captures.len()
+                });
+            }
+            continue;
+        }
+        if !state.advanced && state.reps >= min {
+            state.advanced = true;
+            let index = state.index + 1;
+            let next = state.next.clone();
+            stack.push(State {
+                index,
+                reps: 0,
+                next: next.clone(),
+                candidate: next,
+                advanced: false,
+                captures: captures.len()
+            });
+            continue;
+        }
+        stack.pop();
+    }
+    captures.truncate(len);
+    false
+}
+
+fn is_ident(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-'
+}
+
+struct Parser<'s, F> {
+    source: &'s str,
+    pos: usize,
+    resolve: F,
+    captures: Vec<SmolStr>
+}
+impl<'s, T, F> Parser<'s, F>
+    where F: FnMut(&str) -> Option<T>
+{
+    fn error<V>(&self, kind: QueryErrorKind) -> Result<V, QueryError> {
+        Err(QueryError { offset: self.pos, kind })
+    }
+    /// Skip whitespace and comments, and return the next character
+    fn peek(&mut self) -> Option<char> {
+        loop {
+            let c = self.source[self.pos..].chars().next()?;
+            if c == ';' {
+                let line = self.source[self.pos..].find('\n').unwrap_or(self.source.len() - self.pos);
+                self.pos += line;
+            } else if c.is_whitespace() {
+                self.pos += c.len_utf8();
+            } else {
+                return Some(c);
+            }
+        }
+    }
+    fn bump(&mut self) {
+        if let Some(c) = self.source[self.pos..].chars().next() {
+            self.pos += c.len_utf8();
+        }
+    }
+    fn expect(&mut self, expected: char) -> Result<(), QueryError> {
+        match self.peek() {
+            Some(c) if c == expected => {
+                self.bump();
+                Ok(())
+            },
+            Some(_) => self.error(QueryErrorKind::UnexpectedChar),
+            None => self.error(QueryErrorKind::UnexpectedEnd)
+        }
+    }
+    /// Read an identifier directly at the current position, which may also
+    /// contain the extra characters
+    fn ident(&mut self, extra: &[char]) -> Result<&'s str, QueryError> {
+        let source = self.source;
+        let rest = &source[self.pos..];
+        let len = rest.find(|c: char| !is_ident(c) && !extra.contains(&c)).unwrap_or(rest.len());
+        if len == 0 {
+            return match rest.is_empty() {
+                true => self.error(QueryErrorKind::UnexpectedEnd),
+                false => self.error(QueryErrorKind::UnexpectedChar)
+            };
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+    fn string(&mut self) -> Result<SmolStr, QueryError> {
+        self.expect('"')?;
+        match lex::string(self.source, self.pos) {
+            Some((string, end)) => {
+                self.pos = end;
+                Ok(string)
+            },
+            None => {
+                self.pos = self.source.len();
+                self.error(QueryErrorKind::UnexpectedEnd)
+            }
+        }
+    }
+    fn captures(&mut self) -> Result<Vec<usize>, QueryError> {
+        let mut captures = Vec::new();
+        while self.peek() == Some('@') {
+            self.bump();
+            let name = self.ident(&['.'])?;
+            let index = match self.captures.iter().position(|capture| capture == name) {
+                Some(index) => index,
+                None => {
+                    self.captures.push(name.into());
+                    self.captures.len() - 1
+                }
+            };
+            captures.push(index);
+        }
+        Ok(captures)
+    }
+    fn quantifier(&mut self) -> Quantifier {
+        let quantifier = match self.peek() {
+            Some('?') => Quantifier::ZeroOrOne,
+            Some('*') => Quantifier::ZeroOrMore,
+            Some('+') => Quantifier::OneOrMore,
+            _ => return Quantifier::One
+        };
+        self.bump();
+        quantifier
+    }
+    /// Parse the rest of a predicate, after the opening parenthesis
+    fn predicate(&mut self) -> Result<Predicate, QueryError> {
+        let start = self.pos;
+        self.expect('#')?;
+        let name = self.ident(&['?', '!'])?;
+
+        let mut args = Vec::new();
+        while self.peek() != Some(')') {
+            match self.peek() {
+                Some('@') => {
+                    self.bump();
+                    let name = self.ident(&['.'])?;
+                    match self.captures.iter().position(|capture| capture == name) {
+                        Some(index) => args.push(Arg::Capture(index)),
+                        None => return self.error(QueryErrorKind::UnknownCapture)
+                    }
+                },
+                Some('"') => args.push(Arg::Text(self.string()?)),
+                Some(_) => return self.error(QueryErrorKind::UnexpectedChar),
+                None => return self.error(QueryErrorKind::UnexpectedEnd)
+            }
+        }
+        self.bump();
+
+        let invalid = QueryError { offset: start, kind: QueryErrorKind::InvalidPredicate };
+        let (negate, any_of) = match name {
+            "eq?" => (false, false),
+            "not-eq?" => (true, false),
+            "any-of?" => (false, true),
+            "not-any-of?" => (true, true),
+            _ => return Err(QueryError { offset: start, kind: QueryErrorKind::UnknownPredicate })
+        };
+        let mut args = args.into_iter();
+        let capture = match args.next() {
+            Some(Arg::Capture(capture)) => capture,
+            _ => return Err(invalid)
+        };
+        if any_of {
+            let values = args
+                .map(|arg| match arg {
+                    Arg::Text(text) => Ok(text),
+                    Arg::Capture(_) => Err(invalid)
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            if values.is_empty() {
+                return Err(invalid);
+            }
+            Ok(Predicate::AnyOf { capture, values, negate })
+        } else {
+            match (args.next(), args.next()) {
+                (Some(other), None) => Ok(Predicate::Eq { capture, other, negate }),
+                _ => Err(invalid)
+            }
+        }
+    }
+    /// Parse a pattern without quantifier and captures
+    fn matcher(&mut self, predicates: &mut Vec<Predicate>) -> Result<Matcher<T>, QueryError> {
+        match self.peek() {
+            Some('"') => return Ok(Matcher::Text(self.string()?)),
+            Some('(') => self.bump(),
+            Some(_) => {
+                let start = self.pos;
+                return match self.ident(&[])? {
+                    "_" => Ok(Matcher::Node { kind: None, children: Vec::new(), anchor_end: false }),
+                    _ => Err(QueryError { offset: start, kind: QueryErrorKind::UnexpectedChar })
+                };
+            },
+            None => return self.error(QueryErrorKind::UnexpectedEnd)
+        }
+
+        self.peek();
+        let start = self.pos;
+        let kind = match self.ident(&[])? {
+            "_" => None,
+            name => match (self.resolve)(name) {
+                Some(kind) => Some(kind),
+                None => return Err(QueryError { offset: start, kind: QueryErrorKind::UnknownKind })
+            }
+        };
+
+        let mut children = Vec::new();
+        let mut anchored = false;
+        loop {
+            match self.peek() {
+                Some(')') => {
+                    self.bump();
+                    return Ok(Matcher::Node { kind, children, anchor_end: anchored });
+                },
+                Some('.') => {
+                    self.bump();
+                    anchored = true;
+                },
+                Some('(') if self.source[self.pos+1..].trim_start().starts_with('#') => {
+                    self.bump();
+                    self.peek();
+                    let predicate = self.predicate()?;
+                    predicates.push(predicate);
+                },
+                Some(_) => {
+                    let matcher = self.matcher(predicates)?;
+                    let quantifier = self.quantifier();
+                    let captures = self.captures()?;
+                    children.push(Child {
+                        pattern: Pattern { matcher, captures },
+                        quantifier,
+                        anchored
+                    });
+                    anchored = false;
+                },
+                None => return self.error(QueryErrorKind::UnexpectedEnd)
+            }
+        }
+    }
+    /// Parse a top-level pattern, optionally wrapped in parentheses together
+    /// with predicates, like `((Number) @n (#eq? @n "0"))`
+    fn pattern(&mut self) -> Result<(Pattern<T>, Vec<Predicate>), QueryError> {
+        let mut predicates = Vec::new();
+        let wrapped = self.peek() == Some('(')
+            && self.source[self.pos+1..].trim_start().starts_with(['(', '"']);
+        if wrapped {
+            self.bump();
+        }
+
+        let matcher = self.matcher(&mut predicates)?;
+        let captures = self.captures()?;
+
+        if wrapped {
+            loop {
+                match self.peek() {
+                    Some(')') => break,
+                    Some('(') => {
+                        self.bump();
+                        self.peek();
+                        let predicate = self.predicate()?;
+                        predicates.push(predicate);
+                    },
+                    Some(_) => return self.error(QueryErrorKind::UnexpectedChar),
+                    None => return self.error(QueryErrorKind::UnexpectedEnd)
+                }
+            }
+            self.bump();
+        }
+        Ok((Pattern { matcher, captures }, predicates))
+    }
+}
+
+/// A compiled query consisting of one or more patterns, in a syntax similar
+/// to tree-sitter queries:
+///
+/// - `(Kind child...)` matches a node of the kind with children matching the
+///   child patterns in order. Other children may be between them, unless the
+///   patterns are separated by `.`. A `.` first or last means the pattern
+///   must match the first or last child.
+/// - `_` or `(_ child...)` matches a node of any kind.
+/// - `"text"` matches a leaf with exactly that text.
+/// - `?`, `*` and `+` after a child pattern matches it at most once, any
+///   amount of times or at least once. Repeated matches have to be siblings
+///   directly after each other, so `(Group (Number)+ @n)` on `1 + 2` only
+///   captures `1`.
+/// - `@name` after a pattern captures the node it matched.
+/// - `(#eq? @a @b)`, `(#eq? @a "text")`, `(#any-of? @a "text"...)` and their
+///   negated `#not-` versions compare the text of captures. They can be put
+///   among the child patterns, or next to a top-level pattern by wrapping
+///   both in parentheses: `((Number) @n (#eq? @n "0"))`.
+/// - `;` starts a comment.
+#[derive(Clone, Debug)]
+pub struct Query<T> {
+    patterns: Vec<(Pattern<T>, Vec<Predicate>)>,
+    captures: Vec<SmolStr>
+}
+impl<T: Copy + PartialEq> Query<T> {
+    /// Compile a query. `resolve` is used to turn kind names into kinds, and
+    /// should return None for unknown names.
+    pub fn new<F>(source: &str, resolve: F) -> Result<Self, QueryError>
+        where F: FnMut(&str) -> Option<T>
+    {
+        let mut parser = Parser {
+            source,
+            pos: 0,
+            resolve,
+            captures: Vec::new()
+        };
+        let mut patterns = Vec::new();
+        while parser.peek().is_some() {
+            patterns.push(parser.pattern()?);
+        }
+        Ok(Self {
+            patterns,
+            captures: parser.captures
+        })
+    }
+    /// Get the amount of top-level patterns
+    pub fn pattern_count(&self) -> usize {
+        self.patterns.len()
+    }
+    /// Get the names of all captures, without the `@`
+    pub fn capture_names(&self) -> &[SmolStr] {
+        &self.captures
+    }
+    /// Get an iterator over all matches in this subtree, in order. Each node
+    /// is tried against every pattern, so there may be multiple matches per
+    /// node.
+    pub fn matches<R: TreeRoot<T>>(&self, node: &Node<T, R>) -> QueryMatches<'_, T, R> {
+        QueryMatches {
+            query: self,
+            walker: node.walk(),
+            current: None,
+            pattern: 0
+        }
+    }
+}
+
+/// A node captured by a query
+pub struct QueryCapture<'q, T: Copy, R: TreeRoot<T>> {
+    /// The name of the capture, without the `@`
+    pub name: &'q str,
+    /// The captured node
+    pub node: Node<T, R>,
+    /// The range of the captured node
+    pub range: TextRange
+}
+impl<'q, T: Copy + Debug, R: TreeRoot<T>> Debug for QueryCapture<'q, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "@{} {:?}", self.name, self.node)
+    }
+}
+
+/// A successful match of a query pattern
+pub struct QueryMatch<'q, T: Copy, R: TreeRoot<T>> {
+    /// The index of the pattern that matched
+    pub pattern: usize,
+    /// All captured nodes, in the order they were matched
+    pub captures: Vec<QueryCapture<'q, T, R>>
+}
+impl<'q, T: Copy, R: TreeRoot<T>> QueryMatch<'q, T, R> {
+    /// Get the first node captured with the name
+    pub fn get(&self, name: &str) -> Option<&QueryCapture<'q, T, R>> {
+        self.captures.iter().find(|capture| capture.name == name)
+    }
+    /// Get all nodes captured with the name
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a QueryCapture<'q, T, R>> + 'a {
+        self.captures.iter().filter(move |capture| capture.name == name)
+    }
+}
+impl<'q, T: Copy + Debug, R: TreeRoot<T>> Debug for QueryMatch<'q, T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("QueryMatch")
+            .field("pattern", &self.pattern)
+            .field("captures", &self.captures)
+            .finish()
+    }
+}
+
+/// An iterator over query matches, see `Query::matches`
+pub struct QueryMatches<'q, T: Copy, R: TreeRoot<T>> {
+    query: &'q Query<T>,
+    walker: NodeWalker<T, R>,
+    current: Option<Node<T, R>>,
+    pattern: usize
+}
+impl<'q, T: Copy + PartialEq, R: TreeRoot<T>> Iterator for QueryMatches<'q, T, R> {
+    type Item = QueryMatch<'q, T, R>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = match self.current {
+                Some(ref node) => node.clone(),
+                None => loop {
+                    if let (_, WalkEvent::Enter(node)) = self.walker.next()? {
+                        self.current = Some(node.clone());
+                        self.pattern = 0;
+                        break node;
+                    }
+                }
+            };
+
+            while let Some((pattern, predicates)) = self.query.patterns.get(self.pattern) {
+                self.pattern += 1;
+                let mut captures = Vec::new();
+                if match_pattern(pattern, &node, predicates, &mut captures) {
+                    let query = self.query;
+                    return Some(QueryMatch {
+                        pattern: self.pattern - 1,
+                        captures: captures.into_iter()
+                            .map(|(index, node)| QueryCapture {
+                                name: &query.captures[index],
+                                range: node.range(),
+                                node
+                            })
+                            .collect()
+                    });
+                }
+            }
+            self.current = None;
+        }
+    }
+}
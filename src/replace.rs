@@ -0,0 +1,455 @@
+use crate::{
+    lex,
+    node::{MutableRoot, Node, TreeRoot, WalkEvent}
+};
+
+use smol_str::SmolStr;
+use std::{
+    borrow::Cow,
+    error::Error,
+    fmt::{self, Debug, Display}
+};
+use text_unit::TextRange;
+
+/// What went wrong when compiling a replacement, see `ReplaceError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReplaceErrorKind {
+    /// The pattern or template ended in the middle of a node
+    UnexpectedEnd,
+    /// A character that isn't allowed at this position
+    UnexpectedChar,
+    /// The resolver didn't recognize a kind name
+    UnknownKind,
+    /// The template refers to a capture that isn't in the pattern
+    UnknownCapture,
+    /// The template contains `_` or a leaf without a kind, which can't be
+    /// turned into a node
+    NotBuildable
+}
+
+/// An error returned by `Replace::new` when the pattern or template is
+/// malformed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReplaceError {
+    /// Whether the error is in the template, as opposed to the pattern
+    pub in_template: bool,
+    /// The byte offset in the pattern or template where the error was found
+    pub offset: usize,
+    /// What went wrong
+    pub kind: ReplaceErrorKind
+}
+impl Display for ReplaceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            ReplaceErrorKind::UnexpectedEnd => "unexpected end",
+            ReplaceErrorKind::UnexpectedChar => "unexpected character",
+            ReplaceErrorKind::UnknownKind => "unknown kind",
+            ReplaceErrorKind::UnknownCapture => "unknown capture",
+            ReplaceErrorKind::NotBuildable => "can't build node"
+        };
+        let source = if self.in_template { "template" } else { "pattern" };
+        write!(f, "{} at offset {} of the {}", reason, self.offset, source)
+    }
+}
+impl Error for ReplaceError {}
+
+#[derive(Clone, Debug)]
+enum Element<T> {
+    /// A branch of the kind, with exactly these children
+    Branch(T, Vec<Element<T>>),
+    /// A leaf with the text, and of the kind unless it's None
+    Leaf(Option<T>, SmolStr),
+    /// Any node, which is captured by the name
+    Capture(SmolStr),
+    /// Any node
+    Wildcard
+}
+
+struct Parser<'s, F> {
+    source: &'s str,
+    pos: usize,
+    resolve: F,
+    /// the captures the template may use, or None when parsing the pattern
+    captures: Option<Vec<SmolStr>>
+}
+impl<'s, T, F> Parser<'s, F>
+    where F: FnMut(&str) -> Option<T>
+{
+    fn error<V>(&self, offset: usize, kind: ReplaceErrorKind) -> Result<V, ReplaceError> {
+        Err(ReplaceError { in_template: self.captures.is_some(), offset, kind })
+    }
+    /// Skip whitespace and return the next character
+    fn peek(&mut self) -> Option<char> {
+        let rest = &self.source[self.pos..];
+        self.pos += rest.len() - rest.trim_start().len();
+        self.source[self.pos..].chars().next()
+    }
+    fn ident(&mut self) -> Result<&'s str, ReplaceError> {
+        let source = self.source;
+        let rest = &source[self.pos..];
+        let len = rest.find(|c: char| !c.is_alphanumeric() && c != '_').unwrap_or(rest.len());
+        match len {
+            0 if rest.is_empty() => self.error(self.pos, ReplaceErrorKind::UnexpectedEnd),
+            0 => self.error(self.pos, ReplaceErrorKind::UnexpectedChar),
+            _ => {
+                self.pos += len;
+                Ok(&rest[..len])
+            }
+        }
+    }
+    fn kind(&mut self) -> Result<T, ReplaceError> {
+        let start = self.pos;
+        let name = self.ident()?;
+        match (self.resolve)(name) {
+            Some(kind) => Ok(kind),
+            None => self.error(start, ReplaceErrorKind::UnknownKind)
+        }
+    }
+    fn string(&mut self) -> Result<SmolStr, ReplaceError> {
+        // Skip the opening quote
+        match lex::string(self.source, self.pos + 1) {
+            Some((string, end)) => {
+                self.pos = end;
+                Ok(string)
+            },
+            None => self.error(self.source.len(), ReplaceErrorKind::UnexpectedEnd)
+        }
+    }
+    fn element(&mut self) -> Result<Element<T>, ReplaceError> {
+        let start = match self.peek() {
+            Some(_) => self.pos,
+            None => return self.error(self.pos, ReplaceErrorKind::UnexpectedEnd)
+        };
+        let element = match self.peek() {
+            Some('(') => {
+                self.pos += 1;
+                self.peek();
+                let kind = self.kind()?;
+                let mut children = Vec::new();
+                loop {
+                    match self.peek() {
+                        Some(')') => break,
+                        Some(_) => children.push(self.element()?),
+                        None => return self.error(self.pos, ReplaceErrorKind::UnexpectedEnd)
+                    }
+                }
+                self.pos += 1;
+                Element::Branch(kind, children)
+            },
+            Some('"') => Element::Leaf(None, self.string()?),
+            Some('$') => {
+                self.pos += 1;
+                let name = self.ident()?;
+                if let Some(ref captures) = self.captures {
+                    if !captures.iter().any(|capture| capture == name) {
+                        return self.error(start, ReplaceErrorKind::UnknownCapture);
+                    }
+                }
+                Element::Capture(name.into())
+            },
+            _ if self.source[self.pos..].starts_with('_')
+                    && !self.source[self.pos+1..].starts_with(|c: char| c.is_alphanumeric() || c == '_') => {
+                self.pos += 1;
+                Element::Wildcard
+            },
+            _ => {
+                let kind = self.kind()?;
+                match self.peek() {
+                    Some('"') => Element::Leaf(Some(kind), self.string()?),
+                    Some(_) => return self.error(self.pos, ReplaceErrorKind::UnexpectedChar),
+                    None => return self.error(self.pos, ReplaceErrorKind::UnexpectedEnd)
+                }
+            }
+        };
+        match element {
+            Element::Leaf(None, _) | Element::Wildcard if self.captures.is_some() => {
+                self.error(start, ReplaceErrorKind::NotBuildable)
+            },
+            element => Ok(element)
+        }
+    }
+    /// Parse the whole source as one element
+    fn parse(&mut self) -> Result<Element<T>, ReplaceError> {
+        let element = self.element()?;
+        match self.peek() {
+            Some(_) => self.error(self.pos, ReplaceErrorKind::UnexpectedChar),
+            None => Ok(element)
+        }
+    }
+}
+
+/// Find the names of all captures in the element
+fn capture_names<T>(element: &Element<T>, names: &mut Vec<SmolStr>) {
+    match *element {
+        Element::Branch(_, ref children) => for child in children {
+            capture_names(child, names);
+        },
+        Element::Capture(ref name) => names.push(name.clone()),
+        Element::Leaf(..) | Element::Wildcard => ()
+    }
+}
+
+type MutNode<T> = Node<T, MutableRoot<T>>;
+
+/// Where to insert a new node, relative to an existing one
+enum Place<'a, T: Copy + 'a> {
+    Before(&'a MutNode<T>),
+    After(&'a MutNode<T>),
+    FirstChild(&'a MutNode<T>)
+}
+impl<'a, T: Copy> Place<'a, T> {
+    fn insert(self, kind: T, content: Option<SmolStr>) -> MutNode<T> {
+        match self {
+            Place::Before(node) => node.insert_before(kind, content),
+            Place::After(node) => node.insert_after(kind, content),
+            Place::FirstChild(node) => node.prepend_child(kind, content)
+        }
+    }
+}
+
+/// Insert a copy of the subtree at the place
+fn copy_subtree<T, R>(source: &Node<T, R>, place: Place<T>) -> MutNode<T>
+    where T: Copy,
+          R: TreeRoot<T>
+{
+    let mut place = Some(place);
+    let mut top = None;
+    // Each entry is a copied branch, and its last child copied so far
+    let mut branches: Vec<(MutNode<T>, Option<MutNode<T>>)> = Vec::new();
+    for (_, event) in source.walk() {
+        match event {
+            WalkEvent::Enter(node) => {
+                let kind = node.kind();
+                let text = node.leaf_text_cow().map(Cow::into_owned);
+                let is_leaf = text.is_some();
+                let copy = match branches.last_mut() {
+                    Some((branch, last)) => {
+                        let copy = match *last {
+                            Some(ref last) => last.insert_after(kind, text),
+                            None => branch.prepend_child(kind, text)
+                        };
+                        *last = Some(copy.clone());
+                        copy
+                    },
+                    None => place.take().unwrap().insert(kind, text)
+                };
+                if top.is_none() {
+                    top = Some(copy.clone());
+                }
+                if !is_leaf {
+                    branches.push((copy, None));
+                }
+            },
+            WalkEvent::Leave(node) => if !node.is_leaf() {
+                branches.pop();
+            }
+        }
+    }
+    top.unwrap()
+}
+/// A node matched by `Replace::matches`
+pub struct ReplaceMatch<T: Copy, R: TreeRoot<T>> {
+    /// The matched node
+    pub node: Node<T, R>,
+    /// The range of the matched node
+    pub range: TextRange,
+    /// The nodes captured by each name, without the `$`
+    pub captures: Vec<(SmolStr, Node<T, R>)>
+}
+impl<T: Copy + Debug, R: TreeRoot<T>> Debug for ReplaceMatch<T, R> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ReplaceMatch")
+            .field("node", &self.node)
+            .field("captures", &self.captures)
+            .finish()
+    }
+}
+
+/// A node replaced by `Replace::replace`
+pub struct Replacement<T: Copy> {
+    /// The range of the matched node, before any replacements were made
+    pub range: TextRange,
+    /// The node that was inserted in its place
+    pub node: MutNode<T>
+}
+impl<T: Copy + Debug> Debug for Replacement<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Replacement")
+            .field("range", &self.range)
+            .field("node", &self.node)
+            .finish()
+    }
+}
+
+/// A structural search and replace. Both the pattern and the template are
+/// written like this:
+///
+/// - `(Kind child...)` is a branch of the kind with exactly these children.
+/// - `Kind "text"` is a leaf of the kind with the text.
+/// - `"text"` is a leaf of any kind with the text. Only allowed in patterns.
+/// - `$name` matches any node in patterns, and is replaced by a copy of that
+///   node in templates. If a pattern uses the same name twice, both nodes
+///   must be structurally equal.
+/// - `_` matches any node. Only allowed in patterns.
+///
+/// For example, the pattern `(Add $a "+" Number "0")` with the template `$a`
+/// replaces every addition of zero by just the other operand.
+pub struct Replace<T> {
+    pattern: Element<T>,
+    template: Element<T>,
+    trivia: Option<Box<dyn Fn(T) -> bool>>
+}
+impl<T: Copy + PartialEq> Replace<T> {
+    /// Compile a pattern and its replacement template. `resolve` is used to
+    /// turn kind names into kinds, and should return None for unknown names.
+    pub fn new<F>(pattern: &str, template: &str, mut resolve: F) -> Result<Self, ReplaceError>
+        where F: FnMut(&str) -> Option<T>
+    {
+        let pattern = Parser {
+            source: pattern,
+            pos: 0,
+            resolve: &mut resolve,
+            captures: None
+        }.parse()?;
+        let mut captures = Vec::new();
+        capture_names(&pattern, &mut captures);
+        let template = Parser {
+            source: template,
+            pos: 0,
+            resolve: &mut resolve,
+            captures: Some(captures)
+        }.parse()?;
+
+        Ok(Self {
+            pattern,
+            template,
+            trivia: None
+        })
+    }
+    /// Skip all children for which `is_trivia` returns true when matching,
+    /// and compare repeated captures using `Node::equivalent`. Trivia inside
+    /// the matched nodes is lost when they're replaced, unless it's inside a
+    /// captured node.
+    pub fn trivia<F>(mut self, is_trivia: F) -> Self
+        where F: Fn(T) -> bool + 'static
+    {
+        self.trivia = Some(Box::new(is_trivia));
+        self
+    }
+    fn is_trivia(&self, node: &Node<T, impl TreeRoot<T>>) -> bool {
+        self.trivia.as_ref().is_some_and(|is_trivia| is_trivia(node.kind()))
+    }
+    fn match_element<R: TreeRoot<T>>(&self, element: &Element<T>, node: &Node<T, R>, captures: &mut Vec<(SmolStr, Node<T, R>)>) -> bool {
+        match *element {
+            Element::Branch(kind, ref children) => {
+                if node.kind() != kind || node.is_leaf() {
+                    return false;
+                }
+                let mut nodes = node.children().filter(|child| !self.is_trivia(child));
+                for child in children {
+                    match nodes.next() {
+                        Some(node) => if !self.match_element(child, &node, captures) {
+                            return false;
+                        },
+                        None => return false
+                    }
+                }
+                nodes.next().is_none()
+            },
+            Element::Leaf(kind, ref text) => {
+                kind.is_none_or(|kind| kind == node.kind())
+                    && node.leaf_text_cow().is_some_and(|leaf| *leaf == *text)
+            },
+            Element::Capture(ref name) => {
+                let previous = captures.iter().find(|(capture, _)| capture == name);
+                match (previous, &self.trivia) {
+                    (Some((_, previous)), Some(is_trivia)) => previous.equivalent(node, is_trivia).is_ok(),
+                    (Some((_, previous)), None) => previous.structurally_eq(node),
+                    (None, _) => {
+                        captures.push((name.clone(), node.clone()));
+                        true
+                    }
+                }
+            },
+            Element::Wildcard => true
+        }
+    }
+    /// Return true if this node itself matches the pattern
+    pub fn is_match<R: TreeRoot<T>>(&self, node: &Node<T, R>) -> bool {
+        self.match_element(&self.pattern, node, &mut Vec::new())
+    }
+    /// Find all nodes in this subtree matching the pattern, in order. Nodes
+    /// inside a match aren't matched again, since they would be replaced
+    /// together with it. This doesn't change anything, so it can be used as a
+    /// dry run of `replace`.
+    pub fn matches<R: TreeRoot<T>>(&self, node: &Node<T, R>) -> Vec<ReplaceMatch<T, R>> {
+        let mut matches = Vec::new();
+        let mut walker = node.walk();
+        while let Some((_, event)) = walker.next() {
+            if let WalkEvent::Enter(node) = event {
+                let mut captures = Vec::new();
+                if self.match_element(&self.pattern, &node, &mut captures) {
+                    walker.skip_subtree();
+                    matches.push(ReplaceMatch {
+                        range: node.range(),
+                        node,
+                        captures
+                    });
+                }
+            }
+        }
+        matches
+    }
+    /// Insert a node built from the template at the place
+    fn build(&self, element: &Element<T>, captures: &[(SmolStr, MutNode<T>)], place: Place<T>) -> MutNode<T> {
+        match *element {
+            Element::Branch(kind, ref children) => {
+                let branch = place.insert(kind, None);
+                let mut last: Option<MutNode<T>> = None;
+                for child in children {
+                    let place = match last {
+                        Some(ref last) => Place::After(last),
+                        None => Place::FirstChild(&branch)
+                    };
+                    let node = self.build(child, captures, place);
+                    last = Some(node);
+                }
+                branch
+            },
+            Element::Leaf(Some(kind), ref text) => place.insert(kind, Some(text.clone())),
+            Element::Capture(ref name) => {
+                let (_, node) = captures.iter().find(|(capture, _)| capture == name).unwrap();
+                copy_subtree(node, place)
+            },
+            Element::Leaf(None, _) | Element::Wildcard => unreachable!("checked by the parser")
+        }
+    }
+    /// Replace only this node by the template if it matches the pattern,
+    /// returning the node that was inserted in its place
+    pub fn replace_node(&self, node: &MutNode<T>) -> Option<MutNode<T>> {
+        let mut captures = Vec::new();
+        if !self.match_element(&self.pattern, node, &mut captures) {
+            return None;
+        }
+        let replacement = self.build(&self.template, &captures, Place::Before(node));
+        node.clone().remove();
+        Some(replacement)
+    }
+    /// Replace all nodes in this subtree matching the pattern by the
+    /// template. Each match is replaced by inserting the new node before it
+    /// and then removing it, so handles to the matched nodes are no longer
+    /// alive afterwards. This includes `node` itself if it matches.
+    pub fn replace(&self, node: &MutNode<T>) -> Vec<Replacement<T>> {
+        self.matches(node)
+            .into_iter()
+            .map(|found| {
+                let node = self.build(&self.template, &found.captures, Place::Before(&found.node));
+                found.node.remove();
+                Replacement {
+                    range: found.range,
+                    node
+                }
+            })
+            .collect()
+    }
+}
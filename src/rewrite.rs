@@ -0,0 +1,185 @@
+use crate::{
+    lock::RefCount,
+    node::{MutableRoot, Node},
+    replace::Replace
+};
+
+use smol_str::SmolStr;
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display}
+};
+use text_unit::TextRange;
+
+type MutNode<T> = Node<T, MutableRoot<T>>;
+type Predicate<'f, T> = Box<dyn Fn(&MutNode<T>) -> bool + 'f>;
+type Transform<'f, T> = Box<dyn FnMut(MutNode<T>) -> Option<MutNode<T>> + 'f>;
+
+struct Rule<'f, T: Copy> {
+    name: SmolStr,
+    predicate: Predicate<'f, T>,
+    transform: Transform<'f, T>
+}
+
+/// A rule that fired during `Rewriter::run`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Firing {
+    /// The name of the rule
+    pub rule: SmolStr,
+    /// The pass the rule fired in, starting at 0
+    pub pass: usize,
+    /// The range of the node right before it was transformed. Later changes
+    /// may have moved it.
+    pub range: TextRange
+}
+
+/// What happened during `Rewriter::run`
+pub struct RewriteReport<T: Copy> {
+    /// All rules that fired, in order
+    pub fired: Vec<Firing>,
+    /// The amount of passes over the tree
+    pub passes: usize,
+    /// The node that is now in place of the node the rewriter was run on, or
+    /// None if it was removed
+    pub root: Option<MutNode<T>>
+}
+impl<T: Copy + Debug> Debug for RewriteReport<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RewriteReport")
+            .field("fired", &self.fired)
+            .field("passes", &self.passes)
+            .field("root", &self.root)
+            .finish()
+    }
+}
+
+/// An error returned by `Rewriter::run` when rules still fired in the last
+/// allowed pass, which probably means they never stop
+pub struct RewriteError<T: Copy> {
+    /// What happened until the rewriter gave up
+    pub report: RewriteReport<T>
+}
+impl<T: Copy + Debug> Debug for RewriteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("RewriteError")
+            .field("report", &self.report)
+            .finish()
+    }
+}
+impl<T: Copy> Display for RewriteError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "rules still fired after {} passes", self.report.passes)
+    }
+}
+impl<T: Copy + Debug> Error for RewriteError<T> {}
+
+/// Applies rewrite rules to a mutable tree until none of them fire anymore.
+///
+/// Each pass goes through all nodes in the tree, parents before children, and
+/// applies the first rule whose predicate returns true for the node. Nodes
+/// that were removed by an earlier transformation in the same pass are
+/// skipped, and nodes that were inserted are only looked at in the next pass.
+pub struct Rewriter<'f, T: Copy> {
+    rules: Vec<Rule<'f, T>>,
+    max_passes: usize
+}
+impl<'f, T: Copy> Default for Rewriter<'f, T> {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            max_passes: 100
+        }
+    }
+}
+impl<'f, T: Copy> Rewriter<'f, T> {
+    /// Create a new rewriter without any rules
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Add a rule. When `predicate` returns true for a node, `transform` is
+    /// called with it and should change the tree using for example
+    /// `insert_before`, `insert_after` and `remove`. It returns the node that
+    /// is now in its place, which is the same node if it was changed in
+    /// place, or None if it was removed without replacement.
+    ///
+    /// Rules are tried in the order they were added.
+    pub fn rule<P, F>(mut self, name: &str, predicate: P, transform: F) -> Self
+        where P: Fn(&MutNode<T>) -> bool + 'f,
+              F: FnMut(MutNode<T>) -> Option<MutNode<T>> + 'f
+    {
+        self.rules.push(Rule {
+            name: name.into(),
+            predicate: Box::new(predicate),
+            transform: Box::new(transform)
+        });
+        self
+    }
+    /// Add a rule that replaces nodes matching the pattern of a structural
+    /// replacement with its template, see `Replace`
+    pub fn replace_rule(self, name: &str, replace: Replace<T>) -> Self
+        where T: PartialEq + 'f
+    {
+        let replace = RefCount::new(replace);
+        let matcher = replace.clone();
+        self.rule(
+            name,
+            move |node| matcher.is_match(node),
+            move |node| replace.replace_node(&node)
+        )
+    }
+    /// Set the maximum amount of passes before giving up, which defaults to
+    /// 100. The last pass is the one where no rules fire, so this needs to be
+    /// at least 1.
+    pub fn max_passes(mut self, max_passes: usize) -> Self {
+        self.max_passes = max_passes;
+        self
+    }
+    /// Apply the rules to this subtree until none of them fire. If rules
+    /// still fire after the maximum amount of passes, an error with the
+    /// report so far is returned.
+    pub fn run(&mut self, node: &MutNode<T>) -> Result<RewriteReport<T>, RewriteError<T>> {
+        let mut report = RewriteReport {
+            fired: Vec::new(),
+            passes: 0,
+            root: Some(node.clone())
+        };
+
+        while report.passes < self.max_passes {
+            let root = match report.root {
+                Some(ref root) => root.clone(),
+                None => return Ok(report)
+            };
+            let pass = report.passes;
+            report.passes += 1;
+
+            let mut fired = false;
+            let nodes: Vec<_> = root.descendants().collect();
+            for node in nodes {
+                if !node.is_alive() {
+                    continue;
+                }
+                let rule = match self.rules.iter_mut().find(|rule| (rule.predicate)(&node)) {
+                    Some(rule) => rule,
+                    None => continue
+                };
+                fired = true;
+                report.fired.push(Firing {
+                    rule: rule.name.clone(),
+                    pass,
+                    range: node.range()
+                });
+
+                let is_root = node == root;
+                let replacement = (rule.transform)(node);
+                if is_root {
+                    report.root = replacement;
+                }
+            }
+
+            if !fired {
+                return Ok(report);
+            }
+        }
+        Err(RewriteError { report })
+    }
+}
@@ -0,0 +1,185 @@
+use crate::{
+    builder::{Content, NodeId},
+    node::{Node, NodeWalker, RootData, TreeRoot, WalkEvent}
+};
+
+use std::{
+    collections::hash_map::DefaultHasher,
+    fmt::{self, Debug},
+    hash::{Hash, Hasher}
+};
+
+/// The first difference found by `Node::equivalent`. Either node is None if
+/// that tree ended before the other one.
+pub struct Divergence<T: Copy, R1: TreeRoot<T>, R2: TreeRoot<T>> {
+    /// The differing node in the tree `equivalent` was called on
+    pub left: Option<Node<T, R1>>,
+    /// The differing node in the tree that was passed as argument
+    pub right: Option<Node<T, R2>>
+}
+impl<T: Copy + Debug, R1: TreeRoot<T>, R2: TreeRoot<T>> Debug for Divergence<T, R1, R2> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Divergence")
+            .field("left", &self.left)
+            .field("right", &self.right)
+            .finish()
+    }
+}
+
+/// Get the next walk event, skipping over all trivia nodes and their children
+fn next_non_trivia<T, R, F>(walker: &mut NodeWalker<T, R>, is_trivia: &F) -> Option<WalkEvent<T, R>>
+    where T: Copy,
+          R: TreeRoot<T>,
+          F: Fn(T) -> bool
+{
+    while let Some((_, event)) = walker.next() {
+        match event {
+            WalkEvent::Enter(ref node) if is_trivia(node.kind()) => {
+                // Skip the children and the event of leaving the node
+                walker.skip_subtree();
+                walker.next();
+            },
+            event => return Some(event)
+        }
+    }
+    None
+}
+
+fn event_node<T: Copy, R: TreeRoot<T>>(event: WalkEvent<T, R>) -> Node<T, R> {
+    match event {
+        WalkEvent::Enter(node) | WalkEvent::Leave(node) => node
+    }
+}
+
+impl<T: Copy + Hash> RootData<T> {
+    /// Calculate the fingerprints of all nodes reachable from the top-level
+    /// node of the specified node, children before parents
+    fn calculate_fingerprints(&self, start: NodeId) -> Vec<u64> {
+        let mut fingerprints = vec![0; self.arena.len()];
+
+        let mut current = start;
+        while let Some(next) = self.repr(current).parent.or(self.repr(current).prev_sibling) {
+            current = next;
+        }
+        'outer: loop {
+            // Go down to the first node without children
+            while let Content::Branch(Some(child)) = self.repr(current).content {
+                current = child;
+            }
+            // Calculate fingerprints of all nodes we walk out of
+            loop {
+                let repr = self.repr(current);
+                let mut hasher = DefaultHasher::new();
+                repr.kind.hash(&mut hasher);
+                match repr.content {
+                    Content::Leaf(ref text) => {
+                        0u8.hash(&mut hasher);
+                        text.hash(&mut hasher);
+                    },
+                    Content::Branch(mut child) => {
+                        1u8.hash(&mut hasher);
+                        while let Some(id) = child {
+                            fingerprints[id.index].hash(&mut hasher);
+                            child = self.repr(id).next_sibling;
+                        }
+                    }
+                }
+                fingerprints[current.index] = hasher.finish();
+
+                if let Some(next) = repr.next_sibling {
+                    current = next;
+                    continue 'outer;
+                }
+                match repr.parent {
+                    Some(parent) => current = parent,
+                    None => break 'outer
+                }
+            }
+        }
+
+        fingerprints
+    }
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Return true if both subtrees have the same shape, the same kinds and
+    /// the same leaf text. Unlike `==`, this doesn't care about which trees
+    /// the nodes belong to or where in the tree they are.
+    pub fn structurally_eq<R2: TreeRoot<T>>(&self, other: &Node<T, R2>) -> bool
+        where T: PartialEq
+    {
+        let mut left = self.borrowed().walk();
+        let mut right = other.borrowed().walk();
+        loop {
+            match (left.next(), right.next()) {
+                (None, None) => return true,
+                (Some((_, WalkEvent::Enter(left))), Some((_, WalkEvent::Enter(right)))) => {
+                    if left.kind() != right.kind() || left.leaf_text_cow() != right.leaf_text_cow() {
+                        return false;
+                    }
+                },
+                (Some((_, WalkEvent::Leave(_))), Some((_, WalkEvent::Leave(_)))) => (),
+                _ => return false
+            }
+        }
+    }
+    /// Check if both subtrees are structurally equal when ignoring all trivia,
+    /// such as whitespace and comments. Nodes for which `is_trivia` returns
+    /// true are skipped together with all their children. On mismatch, the
+    /// first pair of nodes that differ is returned.
+    pub fn equivalent<R2, F>(&self, other: &Node<T, R2>, is_trivia: F) -> Result<(), Divergence<T, R, R2>>
+        where T: PartialEq,
+              R2: TreeRoot<T>,
+              F: Fn(T) -> bool
+    {
+        let mut left = self.walk();
+        let mut right = other.walk();
+        loop {
+            let (left, right) = match (next_non_trivia(&mut left, &is_trivia), next_non_trivia(&mut right, &is_trivia)) {
+                (None, None) => return Ok(()),
+                (Some(WalkEvent::Enter(left)), Some(WalkEvent::Enter(right))) => {
+                    if left.kind() == right.kind() && left.leaf_text_cow() == right.leaf_text_cow() {
+                        continue;
+                    }
+                    (Some(left), Some(right))
+                },
+                (Some(WalkEvent::Leave(_)), Some(WalkEvent::Leave(_))) => continue,
+                (left, right) => (left.map(event_node), right.map(event_node))
+            };
+            return Err(Divergence { left, right });
+        }
+    }
+    /// Get a hash of the kinds and text of this subtree, also known as a
+    /// Merkle hash since it's calculated from the fingerprints of all
+    /// children. Structurally equal subtrees always have the same fingerprint,
+    /// so it can be used to quickly find identical or changed subtrees.
+    ///
+    /// The fingerprints of all nodes are calculated the first time this is
+    /// called, and cached until the tree is changed. They aren't guaranteed to
+    /// be the same across different versions of Rust or this library.
+    pub fn fingerprint(&self) -> u64
+        where T: Hash
+    {
+        self.root.with_data(|data| {
+            data.repr(self.node);
+            data.fingerprints.get_or_init(|| data.calculate_fingerprints(self.node))[self.node.index]
+        })
+    }
+}
+
+/// A wrapper around a node that compares and hashes it by its structure using
+/// `Node::structurally_eq` and `Node::fingerprint`, instead of by identity.
+/// Useful for deduplicating identical subtrees using a `HashSet` or `HashMap`.
+#[derive(Clone, Debug)]
+pub struct Structural<N>(pub N);
+impl<T: Copy + PartialEq, R: TreeRoot<T>> PartialEq for Structural<Node<T, R>> {
+    fn eq(&self, other: &Self) -> bool {
+        self.0.structurally_eq(&other.0)
+    }
+}
+impl<T: Copy + Eq, R: TreeRoot<T>> Eq for Structural<Node<T, R>> {}
+impl<T: Copy + Hash, R: TreeRoot<T>> Hash for Structural<Node<T, R>> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.0.fingerprint().hash(state);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::{
+    builder::{Content, NodeId, NodeRepr},
+    node::{Node, RootData, TreeRoot}
+};
+
+use std::{
+    error::Error,
+    fmt::{self, Display}
+};
+
+/// What is wrong with a node, see `ValidationError`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValidationErrorKind {
+    /// A link of the node points to a node that doesn't exist
+    DanglingLink,
+    /// The node was reached twice, meaning the links form a cycle
+    Cycle,
+    /// The parent link doesn't point to the branch the node is a child of
+    WrongParent,
+    /// The previous sibling link doesn't point to the node before it
+    WrongPrevSibling,
+    /// The length doesn't match the leaf text or the sum of all children
+    WrongLength,
+    /// The stored range doesn't start where the previous one ended, or the
+    /// children don't cover the whole range of the parent
+    WrongRange,
+    /// The node is in the arena but can't be reached from the root
+    Unreachable
+}
+
+/// An error returned by `Node::validate` when a tree invariant is broken
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ValidationError {
+    /// The index of the offending node in the arena
+    pub index: usize,
+    /// What is wrong with the node
+    pub kind: ValidationErrorKind
+}
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.kind {
+            ValidationErrorKind::DanglingLink => "links to a node that doesn't exist",
+            ValidationErrorKind::Cycle => "is part of a cycle",
+            ValidationErrorKind::WrongParent => "has the wrong parent",
+            ValidationErrorKind::WrongPrevSibling => "has the wrong previous sibling",
+            ValidationErrorKind::WrongLength => "has the wrong length",
+            ValidationErrorKind::WrongRange => "has the wrong range",
+            ValidationErrorKind::Unreachable => "can't be reached from the root"
+        };
+        write!(f, "node {} {}", self.index, reason)
+    }
+}
+impl Error for ValidationError {}
+
+impl<T: Copy> RootData<T> {
+    fn validate(&self, start: NodeId) -> Result<(), ValidationError> {
+        let error = |id: NodeId, kind| ValidationError { index: id.index, kind };
+        let get = |from: NodeId, id: NodeId| -> Result<&NodeRepr<T>, ValidationError> {
+            self.get(id).ok_or_else(|| error(from, ValidationErrorKind::DanglingLink))
+        };
+        let range = |id: NodeId| self.ranges.get(id.index).map(|&(start, end)| (start, end.unwrap_or(start)));
+
+        if !self.ranges.is_empty() && self.ranges.len() != self.arena.len() {
+            return Err(error(start, ValidationErrorKind::WrongRange));
+        }
+
+        // Find the first top-level node, giving up if we walk more steps than
+        // there are nodes
+        let mut first = start;
+        for _ in 0..=self.arena.len() {
+            let repr = get(first, first)?;
+            match repr.parent.or(repr.prev_sibling) {
+                Some(next) => { get(first, next)?; first = next; },
+                None => break
+            }
+        }
+
+        let mut visited = vec![false; self.arena.len()];
+        // Each entry is a list of siblings to check, and the branch they're
+        // the children of
+        let mut lists = vec![(None, Some(first))];
+        while let Some((parent, first)) = lists.pop() {
+            let mut prev = None;
+            let mut next = first;
+            let mut len = 0;
+            let mut offset = parent.and_then(range).map(|(start, _)| start).unwrap_or(0);
+
+            while let Some(current) = next {
+                let repr = get(prev.or(parent).unwrap_or(current), current)?;
+                if visited[current.index] {
+                    return Err(error(current, ValidationErrorKind::Cycle));
+                }
+                visited[current.index] = true;
+
+                if repr.parent != parent {
+                    return Err(error(current, ValidationErrorKind::WrongParent));
+                }
+                if repr.prev_sibling != prev {
+                    return Err(error(current, ValidationErrorKind::WrongPrevSibling));
+                }
+                match repr.content {
+                    Content::Leaf(ref text) => if text.len() as u32 != repr.len {
+                        return Err(error(current, ValidationErrorKind::WrongLength));
+                    },
+                    Content::Branch(child) => lists.push((Some(current), child))
+                }
+                if let Some((start, end)) = range(current) {
+                    if start != offset || end < start || end - start != repr.len {
+                        return Err(error(current, ValidationErrorKind::WrongRange));
+                    }
+                    offset = end;
+                }
+
+                len += repr.len;
+                prev = Some(current);
+                next = repr.next_sibling;
+            }
+
+            if let Some(parent) = parent {
+                if self.repr(parent).len != len {
+                    return Err(error(parent, ValidationErrorKind::WrongLength));
+                }
+                match range(parent) {
+                    Some((_, end)) if end != offset => return Err(error(parent, ValidationErrorKind::WrongRange)),
+                    _ => ()
+                }
+            }
+        }
+
+        for (index, slot) in self.arena.iter().enumerate() {
+            if slot.repr.is_some() && !visited[index] {
+                return Err(ValidationError { index, kind: ValidationErrorKind::Unreachable });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Check that the whole tree this node belongs to is consistent: all
+    /// parent, child and sibling links agree with each other, lengths and
+    /// ranges add up, and no removed node is left behind in the arena. A
+    /// correct tree should always pass this, so it's mostly useful in tests
+    /// and debug assertions.
+    pub fn validate(&self) -> Result<(), ValidationError> {
+        self.root.with_data(|data| data.validate(self.node))
+    }
+}
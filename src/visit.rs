@@ -0,0 +1,114 @@
+use crate::node::{Node, TreeRoot, WalkEvent};
+
+/// What to do after a `Visitor` hook returns
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum VisitControl {
+    /// Keep walking as usual
+    Continue,
+    /// Don't visit the children of the node that was just entered. The node is
+    /// still left afterwards. Anywhere else, this is the same as `Continue`.
+    SkipChildren,
+    /// Stop walking entirely
+    Stop
+}
+
+/// A set of callbacks for walking a tree using `Node::accept`. All hooks do
+/// nothing by default, so only the interesting ones need to be implemented.
+/// Implement this for any `R: TreeRoot<T>` to support all kinds of roots.
+pub trait Visitor<T: Copy, R: TreeRoot<T>> {
+    /// Called when a branch is entered, before any of its children
+    fn enter(&mut self, _node: &Node<T, R>) -> VisitControl {
+        VisitControl::Continue
+    }
+    /// Called when a branch is left, after all of its children
+    fn leave(&mut self, _node: &Node<T, R>) -> VisitControl {
+        VisitControl::Continue
+    }
+    /// Called for each leaf, instead of `enter` and `leave`
+    fn visit_leaf(&mut self, _node: &Node<T, R>) -> VisitControl {
+        VisitControl::Continue
+    }
+}
+
+type Handler<'f, T, R> = Box<dyn FnMut(&Node<T, R>) -> VisitControl + 'f>;
+
+/// A visitor that calls different closures depending on the kind of the
+/// node, so you don't need to match on kinds by hand. Handlers added with
+/// `on` are called both when entering branches and when visiting leaves.
+pub struct KindDispatch<'f, T: Copy, R: TreeRoot<T>> {
+    enter: Vec<(T, Handler<'f, T, R>)>,
+    leave: Vec<(T, Handler<'f, T, R>)>
+}
+impl<'f, T: Copy, R: TreeRoot<T>> Default for KindDispatch<'f, T, R> {
+    fn default() -> Self {
+        Self {
+            enter: Vec::new(),
+            leave: Vec::new()
+        }
+    }
+}
+impl<'f, T: Copy + PartialEq, R: TreeRoot<T>> KindDispatch<'f, T, R> {
+    /// Create a new dispatcher without any handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Call the closure for each branch entered or leaf visited of the
+    /// specified kind
+    pub fn on<F>(mut self, kind: T, f: F) -> Self
+        where F: FnMut(&Node<T, R>) -> VisitControl + 'f
+    {
+        self.enter.push((kind, Box::new(f)));
+        self
+    }
+    /// Call the closure for each branch left of the specified kind
+    pub fn on_leave<F>(mut self, kind: T, f: F) -> Self
+        where F: FnMut(&Node<T, R>) -> VisitControl + 'f
+    {
+        self.leave.push((kind, Box::new(f)));
+        self
+    }
+    fn dispatch(handlers: &mut [(T, Handler<'f, T, R>)], node: &Node<T, R>) -> VisitControl {
+        let kind = node.kind();
+        match handlers.iter_mut().find(|(handler_kind, _)| *handler_kind == kind) {
+            Some((_, f)) => f(node),
+            None => VisitControl::Continue
+        }
+    }
+}
+impl<'f, T: Copy + PartialEq, R: TreeRoot<T>> Visitor<T, R> for KindDispatch<'f, T, R> {
+    fn enter(&mut self, node: &Node<T, R>) -> VisitControl {
+        Self::dispatch(&mut self.enter, node)
+    }
+    fn leave(&mut self, node: &Node<T, R>) -> VisitControl {
+        Self::dispatch(&mut self.leave, node)
+    }
+    fn visit_leaf(&mut self, node: &Node<T, R>) -> VisitControl {
+        Self::dispatch(&mut self.enter, node)
+    }
+}
+
+impl<T: Copy, R: TreeRoot<T>> Node<T, R> {
+    /// Walk this subtree and call the hooks of the visitor on each node, in
+    /// order. Returns false if the visitor stopped the walk early.
+    pub fn accept<V: Visitor<T, R>>(&self, visitor: &mut V) -> bool {
+        let mut walker = self.walk();
+        while let Some((_, event)) = walker.next() {
+            let control = match event {
+                WalkEvent::Enter(ref node) if node.is_leaf() => visitor.visit_leaf(node),
+                WalkEvent::Enter(ref node) => {
+                    let control = visitor.enter(node);
+                    if control == VisitControl::SkipChildren {
+                        walker.skip_subtree();
+                    }
+                    control
+                },
+                WalkEvent::Leave(ref node) if node.is_leaf() => VisitControl::Continue,
+                WalkEvent::Leave(ref node) => visitor.leave(node)
+            };
+            if control == VisitControl::Stop {
+                return false;
+            }
+        }
+        true
+    }
+}
@@ -0,0 +1,109 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{MutableRoot, Node};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation
+}
+
+fn tree() -> Node<SyntaxKind, MutableRoot<SyntaxKind>> {
+    tree! { mut SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Group {
+                Number "2",
+                Operation "*",
+                Number "3"
+            }
+        }
+    }
+}
+
+#[test]
+fn handles_are_remapped() {
+    let root = tree();
+    let one = root.first_child().unwrap();
+    let plus = one.next_sibling().unwrap();
+    let inner = root.last_child().unwrap();
+    let three = inner.last_child().unwrap();
+    let two = inner.first_child().unwrap();
+
+    // Leave some holes in the arena and fill some of them again
+    two.clone().remove();
+    plus.clone().remove();
+    let zero = one.insert_before(SyntaxKind::Number, Some("0".into()));
+    assert_eq!(root.to_string(), "01*3");
+
+    let other = tree();
+    let compaction = root.compact();
+    assert_eq!(compaction.len(), 6);
+    assert!(!compaction.is_empty());
+    assert_eq!(format!("{:?}", compaction), "Compaction { len: 6 }");
+
+    for old in &[&root, &one, &inner, &three, &zero] {
+        assert!(!old.is_alive());
+        assert!(compaction.get(old).unwrap().is_alive());
+    }
+    let new_root = compaction.get(&root).unwrap();
+    new_root.validate().unwrap();
+    assert_eq!(new_root.to_string(), "01*3");
+    assert_eq!(compaction.get(&zero).unwrap().to_string(), "0");
+    assert_eq!(compaction.get(&three).unwrap().to_string(), "3");
+    assert_eq!(compaction.get(&three).unwrap().parent(), compaction.get(&inner));
+    assert_eq!(compaction.get(&one).unwrap().prev_sibling(), compaction.get(&zero));
+
+    // Nodes removed before the compaction and nodes of other trees have no
+    // new location
+    assert_eq!(compaction.get(&two), None);
+    assert_eq!(compaction.get(&plus), None);
+    assert_eq!(compaction.get(&other), None);
+    assert_eq!(compaction.get(&other.first_child().unwrap()), None);
+}
+
+#[test]
+fn stale_handles_stay_dead() {
+    let root = tree();
+    let inner = root.last_child().unwrap();
+    let two = inner.first_child().unwrap();
+    let three = inner.last_child().unwrap();
+    let one = root.first_child().unwrap();
+
+    one.clone().remove();
+    assert!(!one.is_alive());
+
+    let compaction = root.compact();
+    for old in &[&root, &inner, &two, &three, &one] {
+        assert!(!old.is_alive());
+        assert!(old.try_kind().is_err());
+    }
+    assert_eq!(compaction.get(&one), None);
+
+    // New nodes reuse indices past the end of the compacted arena, which
+    // removed and moved nodes used to live in
+    let root = compaction.get(&root).unwrap();
+    for text in &["4", "5", "6", "7", "8", "9"] {
+        root.append_child(SyntaxKind::Number, Some((*text).into()));
+    }
+    root.validate().unwrap();
+    assert_eq!(root.to_string(), "+2*3456789");
+    for old in &[&inner, &two, &three, &one] {
+        assert!(!old.is_alive());
+        assert!(old.try_kind().is_err());
+        assert!(old.try_parent().is_err());
+    }
+
+    // The same holds for a second compaction
+    let compaction = root.compact();
+    assert!(!root.is_alive());
+    assert!(!two.is_alive());
+    let root = compaction.get(&root).unwrap();
+    root.append_child(SyntaxKind::Number, Some("0".into()));
+    assert!(!two.is_alive());
+    assert!(!one.is_alive());
+    root.validate().unwrap();
+}
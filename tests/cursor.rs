@@ -0,0 +1,123 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{Node, TextRange, TreeCursor, TreeRoot};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Move {
+    FirstChild,
+    NextSibling,
+    PrevSibling,
+    Parent
+}
+use Move::*;
+
+/// Go from the first leaf to the last one and back up, then climb past the
+/// start node to the root
+const MOVES: &[Move] = &[
+    FirstChild, NextSibling, NextSibling, FirstChild, FirstChild, NextSibling,
+    NextSibling, NextSibling, PrevSibling, PrevSibling, PrevSibling, Parent,
+    NextSibling, NextSibling, FirstChild, PrevSibling, Parent, PrevSibling,
+    PrevSibling, FirstChild, Parent, Parent, Parent
+];
+
+/// Apply the moves to the cursor, returning whether each one succeeded and
+/// the range after it
+fn cursor_moves<T: Copy>(cursor: &mut TreeCursor<T>) -> Vec<(bool, TextRange)> {
+    let mut result = vec![(true, cursor.range())];
+    for &step in MOVES {
+        let moved = match step {
+            FirstChild => cursor.goto_first_child(),
+            NextSibling => cursor.goto_next_sibling(),
+            PrevSibling => cursor.goto_prev_sibling(),
+            Parent => cursor.goto_parent()
+        };
+        result.push((moved, cursor.range()));
+    }
+    result
+}
+/// Apply the moves to a node the same way
+fn node_moves<R: TreeRoot<SyntaxKind>>(mut node: Node<SyntaxKind, R>) -> Vec<(bool, TextRange)> {
+    let mut result = vec![(true, node.range())];
+    for &step in MOVES {
+        let next = match step {
+            FirstChild => node.first_child(),
+            NextSibling => node.next_sibling(),
+            PrevSibling => node.prev_sibling(),
+            Parent => node.parent()
+        };
+        let moved = next.is_some();
+        if let Some(next) = next {
+            node = next;
+        }
+        result.push((moved, node.range()));
+    }
+    result
+}
+
+#[test]
+fn ranges_on_owned_tree() {
+    let root = tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Group {
+                Group { Number "23", Operation "*", Number "4" },
+                Operation "-",
+                Group {}
+            },
+            Operation "/",
+            Number "56"
+        }
+    };
+    let start = root.children().nth(2).unwrap();
+    let expected = node_moves(start.clone());
+    assert_eq!(expected[expected.len() - 1].1, root.range());
+    assert!(!expected[expected.len() - 1].0);
+
+    let mut cursor = root.borrowed().cursor();
+    assert_eq!(cursor_moves(&mut cursor), node_moves(root.clone()));
+    assert_eq!(start.with_cursor(cursor_moves), expected);
+    let mut cursor = start.borrowed().cursor();
+    assert_eq!(cursor_moves(&mut cursor), expected);
+}
+
+#[test]
+fn ranges_on_edited_tree() {
+    let owned = tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Group {
+                Group { Number "23", Operation "*", Number "4" },
+                Operation "-",
+                Group {}
+            }
+        }
+    };
+    let root = owned.thaw();
+    let inner = root.last_child().unwrap();
+    // Change lengths before, inside and after the node the cursor starts at
+    root.first_child().unwrap().insert_before(SyntaxKind::Number, Some("789".into()));
+    inner.first_child().unwrap().first_child().unwrap().remove();
+    inner.last_child().unwrap().append_child(SyntaxKind::Number, Some("0".into()));
+    inner.insert_after(SyntaxKind::Operation, Some("/".into()));
+    assert_eq!(root.to_string(), "7891+*4-0/");
+
+    assert_eq!(root.with_cursor(cursor_moves), node_moves(root.clone()));
+    assert_eq!(inner.with_cursor(cursor_moves), node_moves(inner.clone()));
+    let leaf = inner.first_child().unwrap().last_child().unwrap();
+    assert_eq!(leaf.with_cursor(cursor_moves), node_moves(leaf.clone()));
+
+    // The frozen original is unchanged
+    let start = owned.last_child().unwrap();
+    let mut cursor = start.borrowed().cursor();
+    assert_eq!(cursor_moves(&mut cursor), node_moves(start.clone()));
+}
@@ -0,0 +1,60 @@
+#![cfg(feature = "derive")]
+
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::SyntaxKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SyntaxKind)]
+enum Kind {
+    #[syntax(trivia)]
+    Whitespace,
+    #[syntax(token, name = "number")]
+    Number,
+    Group
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SyntaxKind)]
+#[repr(i16)]
+enum Signed {
+    Low = -3,
+    Zero = 0,
+    High = 0x7fff
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, SyntaxKind)]
+enum Wide {
+    First = 0xfffe,
+    Last
+}
+
+#[test]
+fn names_and_flags() {
+    assert_eq!(Kind::Whitespace.name(), "Whitespace");
+    assert_eq!(Kind::Number.name(), "number");
+    assert_eq!(Kind::from_name("number"), Some(Kind::Number));
+    assert_eq!(Kind::from_name("Number"), None);
+    assert!(Kind::Whitespace.is_trivia() && !Kind::Group.is_trivia());
+    assert!(Kind::Number.is_token() && !Kind::Group.is_token());
+}
+
+#[test]
+fn raw_values() {
+    for &kind in &[Kind::Whitespace, Kind::Number, Kind::Group] {
+        assert_eq!(Kind::from_raw(kind.into_raw()), Some(kind));
+    }
+    assert_eq!(Kind::from_raw(3), None);
+    for &kind in &[Signed::Low, Signed::Zero, Signed::High] {
+        assert_eq!(Signed::from_raw(kind.into_raw()), Some(kind));
+    }
+    assert_eq!(Wide::Last.into_raw(), u16::MAX);
+    assert_eq!(Wide::from_raw(u16::MAX), Some(Wide::Last));
+}
+
+#[test]
+fn names_in_trees() {
+    let root = tree! { Kind: Group { Number "1", Whitespace " " } };
+    assert_eq!(root.kind_name(), "Group");
+    assert_eq!(root.first_child().unwrap().debug_name(), "number@[0; 1)");
+    assert_eq!(root.dump().to_string(), "Group@[0; 2)\n  number@[0; 1) \"1\"\n  Whitespace@[1; 2) \" \"\n");
+}
@@ -0,0 +1,162 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{FoldCache, Node, OwnedRoot, TreeBuilder};
+use std::cell::{Cell, RefCell};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation
+}
+
+type OwnedNode = Node<SyntaxKind, OwnedRoot<SyntaxKind>>;
+
+fn tree() -> OwnedNode {
+    tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Group {
+                Number "2",
+                Operation "*",
+                Group { Number "3" }
+            },
+            Group {}
+        }
+    }
+}
+
+/// Wrap each branch in parentheses
+fn parenthesize(node: &OwnedNode) -> String {
+    node.fold(
+        |leaf| leaf.to_string(),
+        |_, children| format!("({})", children.concat())
+    )
+}
+
+#[test]
+fn children_before_parent() {
+    let root = tree();
+    let calls = RefCell::new(Vec::new());
+    let value = root.fold(
+        |leaf| {
+            calls.borrow_mut().push(leaf.to_string());
+            1
+        },
+        |branch, children| {
+            calls.borrow_mut().push(format!("({})", branch));
+            children.iter().sum::<usize>() + 1
+        }
+    );
+    assert_eq!(value, 9);
+    assert_eq!(*calls.borrow(), ["1", "+", "2", "*", "3", "(3)", "(2*3)", "()", "(1+2*3)"]);
+
+    assert_eq!(parenthesize(&root), "(1+(2*(3))())");
+    assert_eq!(parenthesize(&root.children().nth(2).unwrap()), "(2*(3))");
+    assert_eq!(parenthesize(&root.first_child().unwrap()), "1");
+    assert_eq!(parenthesize(&root.last_child().unwrap()), "()");
+}
+
+#[test]
+fn deep_tree() {
+    const DEPTH: usize = 100_000;
+
+    let mut builder = TreeBuilder::new();
+    for _ in 0..DEPTH {
+        builder.start_internal(SyntaxKind::Group);
+    }
+    builder.leaf(SyntaxKind::Number, "1".into());
+    for _ in 0..DEPTH {
+        builder.leaf(SyntaxKind::Number, "2".into());
+        builder.finish_internal();
+    }
+    let root = builder.finish();
+
+    let depth = root.fold(|_| 0, |_, children| children.into_iter().max().unwrap() + 1);
+    assert_eq!(depth, DEPTH);
+    let leaves = root.fold(|_| 1, |_, children| children.into_iter().sum::<usize>());
+    assert_eq!(leaves, DEPTH + 1);
+}
+
+#[test]
+fn cached_values_are_reused() {
+    let root = tree();
+    let inner = root.children().nth(2).unwrap();
+    let leaves = Cell::new(0);
+    let branches = Cell::new(0);
+    let fold = |node: &OwnedNode, cache: &mut FoldCache<SyntaxKind, String>| {
+        node.fold_cached(
+            cache,
+            |leaf| {
+                leaves.set(leaves.get() + 1);
+                leaf.to_string()
+            },
+            |_, children| {
+                branches.set(branches.get() + 1);
+                format!("({})", children.concat())
+            }
+        )
+    };
+
+    let mut cache = FoldCache::new();
+    assert_eq!(fold(&root, &mut cache), "(1+(2*(3))())");
+    assert_eq!((leaves.get(), branches.get()), (5, 4));
+
+    // Calling it again, or on a subtree, is free
+    assert_eq!(fold(&root, &mut cache), "(1+(2*(3))())");
+    assert_eq!(fold(&inner, &mut cache), "(2*(3))");
+    assert_eq!(fold(&inner.last_child().unwrap(), &mut cache), "(3)");
+    assert_eq!((leaves.get(), branches.get()), (5, 4));
+    assert_eq!(cache.get(&inner).map(String::as_str), Some("(2*(3))"));
+
+    // Folding a subtree first only leaves the rest for the root
+    let mut cache = FoldCache::new();
+    assert_eq!(cache.get(&inner), None);
+    assert_eq!(fold(&inner, &mut cache), "(2*(3))");
+    assert_eq!((leaves.get(), branches.get()), (5 + 3, 4 + 2));
+    assert_eq!(cache.get(&root), None);
+    assert_eq!(fold(&root, &mut cache), "(1+(2*(3))())");
+    assert_eq!((leaves.get(), branches.get()), (5 + 5, 4 + 4));
+}
+
+#[test]
+fn cache_is_per_tree() {
+    let first = tree();
+    let second = tree! { SyntaxKind:
+        Group { Number "4", Operation "-", Number "5" }
+    };
+    let calls = Cell::new(0);
+    let fold = |node: &OwnedNode, cache: &mut FoldCache<SyntaxKind, String>| {
+        node.fold_cached(
+            cache,
+            |leaf| {
+                calls.set(calls.get() + 1);
+                leaf.to_string()
+            },
+            |_, children| {
+                calls.set(calls.get() + 1);
+                children.concat()
+            }
+        )
+    };
+
+    let mut cache = FoldCache::new();
+    assert_eq!(fold(&first, &mut cache), "1+2*3");
+    assert_eq!(calls.get(), 9);
+    // The second tree uses the same arena indices, but none of the values
+    assert_eq!(cache.get(&second), None);
+    assert_eq!(fold(&second, &mut cache), "4-5");
+    assert_eq!(calls.get(), 9 + 4);
+    assert_eq!(cache.get(&first), None);
+
+    // A thawed and frozen copy of the first tree is a different tree too
+    let copy = first.thaw().freeze();
+    assert_eq!(fold(&first, &mut cache), "1+2*3");
+    assert_eq!(fold(&copy, &mut cache), "1+2*3");
+    assert_eq!(calls.get(), 9 + 4 + 9 + 9);
+
+    cache.clear();
+    assert_eq!(cache.get(&copy), None);
+}
@@ -0,0 +1,112 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{Direction, TreeBuilder};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number
+}
+
+#[test]
+fn tokens_skip_empty_branches() {
+    let root = tree! { SyntaxKind:
+        Group {
+            Group {},
+            Group { Group {}, Number "1" },
+            Number "2",
+            Group { Number "3", Group {} },
+            Group {}
+        }
+    };
+    assert_eq!(root.first_token().unwrap().to_string(), "1");
+    assert_eq!(root.last_token().unwrap().to_string(), "3");
+    assert_eq!(root.first_child().unwrap().first_token(), None);
+
+    let tokens: Vec<_> = std::iter::successors(root.first_token(), |token| token.next_token())
+        .map(|token| token.to_string())
+        .collect();
+    assert_eq!(tokens, ["1", "2", "3"]);
+    let tokens: Vec<_> = std::iter::successors(root.last_token(), |token| token.prev_token())
+        .map(|token| token.to_string())
+        .collect();
+    assert_eq!(tokens, ["3", "2", "1"]);
+}
+
+#[test]
+fn tokens_in_deep_tree() {
+    const DEPTH: usize = 100_000;
+
+    let mut builder = TreeBuilder::new();
+    builder.start_internal(SyntaxKind::Group);
+    for _ in 0..DEPTH {
+        builder.start_internal(SyntaxKind::Group);
+    }
+    builder.leaf(SyntaxKind::Number, "1".into());
+    for _ in 0..DEPTH {
+        builder.finish_internal();
+        builder.start_internal(SyntaxKind::Group);
+        builder.finish_internal();
+    }
+    builder.start_internal(SyntaxKind::Group);
+    builder.leaf(SyntaxKind::Number, "2".into());
+    builder.finish_internal();
+    builder.finish_internal();
+    let root = builder.finish();
+
+    let first = root.first_token().unwrap();
+    assert_eq!(first.to_string(), "1");
+    assert_eq!(root.last_token().unwrap().to_string(), "2");
+    assert_eq!(first.next_token().unwrap().to_string(), "2");
+    assert_eq!(root.last_token().unwrap().prev_token(), Some(first));
+}
+
+#[test]
+fn ancestors_siblings_and_leaves() {
+    let root = tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Group {
+                Number "2",
+                Group { Number "3" }
+            },
+            Number "4"
+        }
+    };
+    let text = |nodes: &mut dyn Iterator<Item = _>| -> Vec<String> {
+        nodes.map(|node: rowan2::Node<_, _>| node.to_string()).collect()
+    };
+    let inner = root.children().nth(1).unwrap();
+    let three = inner.last_child().unwrap().first_child().unwrap();
+
+    // Ancestors start at the parent and end at the root
+    assert_eq!(text(&mut three.ancestors()), ["3", "23", "1234"]);
+    assert_eq!(three.ancestors().last(), Some(root.clone()));
+    assert_eq!(root.ancestors().next(), None);
+
+    // Siblings include the node itself, and nothing past the first or last
+    // child
+    let first = root.first_child().unwrap();
+    let last = root.last_child().unwrap();
+    assert_eq!(text(&mut first.siblings(Direction::Next)), ["1", "23", "4"]);
+    assert_eq!(text(&mut first.siblings(Direction::Prev)), ["1"]);
+    assert_eq!(text(&mut last.siblings(Direction::Next)), ["4"]);
+    assert_eq!(text(&mut last.siblings(Direction::Prev)), ["4", "23", "1"]);
+    assert_eq!(text(&mut inner.siblings(Direction::Prev)), ["23", "1"]);
+    assert_eq!(text(&mut root.siblings(Direction::Next)), ["1234"]);
+
+    assert_eq!(text(&mut root.children_rev()), ["4", "23", "1"]);
+    assert_eq!(text(&mut inner.children_rev()), ["3", "2"]);
+    assert_eq!(first.children_rev().next(), None);
+    assert_eq!(three.parent().unwrap().children_rev().count(), 1);
+
+    assert_eq!(text(&mut root.leaves()), ["1", "2", "3", "4"]);
+    assert_eq!(text(&mut inner.leaves()), ["2", "3"]);
+    let mut leaves = first.leaves();
+    assert_eq!(leaves.next().as_ref(), Some(&first));
+    assert_eq!(leaves.next(), None);
+    let mut tokens = three.tokens();
+    assert_eq!(tokens.next().as_ref(), Some(&three));
+    assert_eq!(tokens.next(), None);
+}
@@ -0,0 +1,143 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{Node, TextRange, TreeRoot};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation,
+    Ws
+}
+
+/// `1+23*4` with empty whitespace leaves right before and after `23`, either
+/// immutable or mutable
+macro_rules! example {
+    ($($mut:ident)?) => {
+        tree! { $($mut)? SyntaxKind:
+            Group {
+                Number "1",
+                Operation "+",
+                Ws "",
+                Group { Number "23", Ws "" },
+                Operation "*",
+                Number "4"
+            }
+        }
+    };
+}
+
+fn range(start: u32, end: u32) -> TextRange {
+    TextRange::from_to(start.into(), end.into())
+}
+
+fn tokens_at<R: TreeRoot<SyntaxKind>>(node: &Node<SyntaxKind, R>, offset: u32) -> Vec<String> {
+    node.token_at_offset(offset.into())
+        .map(|token| token.to_string())
+        .collect()
+}
+
+fn check_token_at_offset<R: TreeRoot<SyntaxKind>>(root: &Node<SyntaxKind, R>) {
+    assert_eq!(tokens_at(root, 0), ["1"]);
+    assert_eq!(tokens_at(root, 1), ["1", "+"]);
+    // Empty leaves on a border are skipped, even inside of another branch
+    assert_eq!(tokens_at(root, 2), ["+", "23"]);
+    assert_eq!(tokens_at(root, 3), ["23"]);
+    assert_eq!(tokens_at(root, 4), ["23", "*"]);
+    assert_eq!(tokens_at(root, 6), ["4"]);
+    assert!(tokens_at(root, 7).is_empty());
+
+    let between = root.token_at_offset(4.into());
+    assert_eq!(between.clone().left_biased().unwrap().kind(), SyntaxKind::Number);
+    assert_eq!(between.right_biased().unwrap().kind(), SyntaxKind::Operation);
+
+    // Only leaves of the node itself are returned
+    let inner = root.children().nth(3).unwrap();
+    assert_eq!(tokens_at(&inner, 2), ["23"]);
+    assert_eq!(tokens_at(&inner, 4), ["23"]);
+    assert!(tokens_at(&inner, 1).is_empty());
+    assert!(tokens_at(&inner, 5).is_empty());
+    // A node without any text has no leaves at all
+    assert!(tokens_at(&root.children().nth(2).unwrap(), 2).is_empty());
+}
+
+fn check_covering_element<R: TreeRoot<SyntaxKind>>(root: &Node<SyntaxKind, R>) {
+    let covering = |range| root.covering_element(range);
+    let inner = root.children().nth(3).unwrap();
+    let number = inner.first_child().unwrap();
+
+    // Empty ranges at the start of the first leaf and the end of the last
+    // leaf return that leaf
+    assert_eq!(covering(range(0, 0)), root.first_child());
+    assert_eq!(covering(range(6, 6)), root.last_child());
+    // Empty ranges on a border return the parent of both sides
+    assert_eq!(covering(range(1, 1)).as_ref(), Some(root));
+    assert_eq!(covering(range(2, 2)).as_ref(), Some(root));
+    assert_eq!(covering(range(4, 4)).as_ref(), Some(root));
+    assert_eq!(inner.covering_element(range(4, 4)), Some(inner.clone()));
+    // Otherwise it's the smallest node containing the range
+    assert_eq!(covering(range(3, 3)), Some(number.clone()));
+    assert_eq!(covering(range(2, 4)), Some(number.clone()));
+    assert_eq!(covering(range(2, 5)).as_ref(), Some(root));
+    assert_eq!(covering(range(0, 6)).as_ref(), Some(root));
+    // Ranges outside of the node
+    assert_eq!(covering(range(5, 7)), None);
+    assert_eq!(inner.covering_element(range(1, 3)), None);
+}
+
+#[test]
+fn token_at_offset() {
+    check_token_at_offset(&example!());
+    check_token_at_offset(&example!(mut));
+}
+
+#[test]
+fn covering_element() {
+    check_covering_element(&example!());
+    check_covering_element(&example!(mut));
+}
+
+#[test]
+fn lookup_after_edits() {
+    let root = example!(mut);
+    let inner = root.children().nth(3).unwrap();
+    inner.first_child().unwrap().insert_after(SyntaxKind::Number, Some("5".into()));
+    root.first_child().unwrap().remove();
+    assert_eq!(root.to_string(), "+235*4");
+
+    assert_eq!(tokens_at(&root, 1), ["+", "23"]);
+    assert_eq!(tokens_at(&root, 3), ["23", "5"]);
+    assert_eq!(tokens_at(&root, 4), ["5", "*"]);
+    assert_eq!(root.covering_element(range(3, 3)), Some(inner.clone()));
+    assert_eq!(root.covering_element(range(3, 4)).unwrap().to_string(), "5");
+}
+
+fn check_find_node_at_range<R: TreeRoot<SyntaxKind>>(root: &Node<SyntaxKind, R>) {
+    let any = |_: &Node<SyntaxKind, R>| true;
+    let group = |node: &Node<SyntaxKind, R>| node.kind() == SyntaxKind::Group;
+    let inner = root.children().nth(3).unwrap();
+    let number = inner.first_child().unwrap();
+
+    // An exact match
+    assert_eq!(root.find_node_at_range(range(2, 4), any), Some(number.clone()));
+    assert_eq!(root.find_node_at_range(range(2, 4), group), Some(inner.clone()));
+    assert_eq!(root.find_node_at_range(range(4, 5), any).unwrap().to_string(), "*");
+    // A range spanning siblings returns their common ancestor
+    assert_eq!(root.find_node_at_range(range(0, 2), any).as_ref(), Some(root));
+    assert_eq!(root.find_node_at_range(range(3, 5), any).as_ref(), Some(root));
+    assert_eq!(root.find_node_at_range(range(1, 3), group).as_ref(), Some(root));
+    // Parents are searched even beyond the node it was called on
+    assert_eq!(number.find_node_at_range(range(3, 3), group), Some(inner.clone()));
+    assert_eq!(root.find_node_at_range(range(3, 3), |node| node.kind() == SyntaxKind::Ws), None);
+    // A range running past the end of the node
+    assert_eq!(root.find_node_at_range(range(5, 7), any), None);
+    assert_eq!(root.find_node_at_range(range(7, 7), any), None);
+    assert_eq!(inner.find_node_at_range(range(3, 5), any), None);
+}
+
+#[test]
+fn find_node_at_range() {
+    check_find_node_at_range(&example!());
+    check_find_node_at_range(&example!(mut));
+}
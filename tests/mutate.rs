@@ -0,0 +1,154 @@
+#[macro_use]
+extern crate rowan2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation
+}
+
+#[test]
+fn edits_keep_tree_valid() {
+    let root = tree! { mut SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Group {
+                Number "2",
+                Operation "*",
+                Number "3"
+            }
+        }
+    };
+    root.validate().unwrap();
+
+    let one = root.first_child().unwrap();
+    let zero = one.insert_before(SyntaxKind::Number, Some("0".into()));
+    root.validate().unwrap();
+    let plus = one.insert_after(SyntaxKind::Operation, Some("+".into()));
+    root.validate().unwrap();
+    zero.remove();
+    root.validate().unwrap();
+
+    let empty = plus.insert_after(SyntaxKind::Group, None);
+    root.validate().unwrap();
+    let four = empty.append_child(SyntaxKind::Number, Some("4".into()));
+    root.validate().unwrap();
+    empty.prepend_child(SyntaxKind::Operation, Some("-".into()));
+    root.validate().unwrap();
+    empty.insert_child(1, SyntaxKind::Number, Some("5".into()));
+    root.validate().unwrap();
+    assert_eq!(empty.to_string(), "-54");
+
+    four.remove();
+    root.validate().unwrap();
+    one.remove();
+    root.validate().unwrap();
+
+    // Removing a branch frees all of its descendants
+    let inner = root.children().last().unwrap();
+    let nested = inner.first_child().unwrap().insert_after(SyntaxKind::Group, None);
+    nested.append_child(SyntaxKind::Number, Some("6".into()));
+    root.validate().unwrap();
+    inner.remove();
+    root.validate().unwrap();
+
+    assert_eq!(root.to_string(), "+-5+");
+    let group = root.children().nth(1).unwrap();
+    assert_eq!(group.kind(), SyntaxKind::Group);
+    assert_eq!(group.range(), rowan2::TextRange::from_to(1.into(), 3.into()));
+    assert_eq!(group.next_sibling().unwrap().prev_sibling(), Some(group.clone()));
+    root.freeze().validate().unwrap();
+}
+
+#[test]
+fn freeze_makes_a_new_tree() {
+    let owned = tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Number "2"
+        }
+    };
+
+    // Even an unchanged tree is a different tree after thawing and freezing
+    let thawed = owned.last_child().unwrap().thaw();
+    let frozen = thawed.freeze();
+    assert!(!frozen.same_tree(&owned));
+    assert!(!frozen.same_tree(&thawed));
+    assert_ne!(frozen, owned.last_child().unwrap());
+    assert!(frozen.id_eq(&owned.last_child().unwrap()));
+    assert_eq!(frozen.to_string(), "2");
+    assert_eq!(frozen.range(), rowan2::TextRange::from_to(2.into(), 3.into()));
+    assert!(frozen.parent().unwrap().structurally_eq(&owned));
+    frozen.validate().unwrap();
+
+    // Changing the thawed tree leaves both frozen trees alone
+    thawed.insert_after(SyntaxKind::Number, Some("3".into()));
+    let changed = thawed.freeze();
+    assert!(!changed.same_tree(&frozen));
+    assert_eq!(changed.parent().unwrap().to_string(), "1+23");
+    assert_eq!(frozen.parent().unwrap().to_string(), "1+2");
+    assert_eq!(owned.to_string(), "1+2");
+}
+
+#[test]
+#[should_panic(expected = "node has been removed from the tree")]
+fn freeze_removed_node() {
+    let root = tree! { mut SyntaxKind: Group { Number "1" } };
+    let one = root.first_child().unwrap();
+    one.clone().remove();
+    one.freeze();
+}
+
+#[test]
+#[should_panic(expected = "can't freeze more than one root node")]
+fn freeze_multiple_roots() {
+    let root = tree! { mut SyntaxKind: Group { Number "1" } };
+    root.insert_after(SyntaxKind::Group, None);
+    root.freeze();
+}
+
+#[test]
+fn removed_handles_stay_dead_when_slots_are_reused() {
+    let root = tree! { mut SyntaxKind:
+        Group {
+            Number "1",
+            Group { Number "2", Number "3" }
+        }
+    };
+    let one = root.first_child().unwrap();
+    let inner = root.last_child().unwrap();
+    let three = inner.last_child().unwrap();
+
+    // Each removed slot goes on the free list, and new nodes take them back
+    // from it, so these end up in the slots of the removed nodes
+    one.clone().remove();
+    let four = root.prepend_child(SyntaxKind::Number, Some("4".into()));
+    inner.clone().remove();
+    let five = root.append_child(SyntaxKind::Group, None);
+    let six = five.append_child(SyntaxKind::Number, Some("6".into()));
+    let seven = five.append_child(SyntaxKind::Number, Some("7".into()));
+    root.validate().unwrap();
+    assert_eq!(root.to_string(), "467");
+
+    for old in &[&one, &inner, &three] {
+        assert!(!old.is_alive());
+        assert_eq!(old.try_kind(), Err(rowan2::NodeRemoved));
+        assert_eq!(old.try_parent(), Err(rowan2::NodeRemoved));
+        assert_eq!(old.try_range(), None);
+        assert_ne!(*old, &four);
+    }
+    for new in &[&four, &five, &six, &seven] {
+        assert!(new.is_alive());
+    }
+
+    // Removing and reusing the same slot again doesn't bring back old handles
+    four.clone().remove();
+    let eight = root.prepend_child(SyntaxKind::Number, Some("8".into()));
+    assert!(!four.is_alive());
+    assert!(!one.is_alive());
+    assert_eq!(eight.to_string(), "8");
+    root.validate().unwrap();
+}
@@ -0,0 +1,137 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{Node, OwnedRoot, Query, QueryError, QueryErrorKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Op,
+    Ws
+}
+
+fn resolve(name: &str) -> Option<SyntaxKind> {
+    match name {
+        "Group" => Some(SyntaxKind::Group),
+        "Number" => Some(SyntaxKind::Number),
+        "Op" => Some(SyntaxKind::Op),
+        "Ws" => Some(SyntaxKind::Ws),
+        _ => None
+    }
+}
+
+/// `1 + 0` followed by `(2 3) 4`
+fn tree() -> Node<SyntaxKind, OwnedRoot<SyntaxKind>> {
+    tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Ws " ",
+            Op "+",
+            Ws " ",
+            Number "0",
+            Group {
+                Number "2",
+                Number "3"
+            },
+            Number "4"
+        }
+    }
+}
+
+/// Get the texts of all captures with the name, for each match
+fn captures(query: &str, name: &str) -> Vec<Vec<String>> {
+    let query = Query::new(query, resolve).unwrap();
+    let root = tree();
+    let matches = query.matches(&root)
+        .map(|found| found.get_all(name).map(|capture| capture.node.to_string()).collect())
+        .collect();
+    matches
+}
+
+fn error(query: &str) -> QueryError {
+    Query::new(query, resolve).unwrap_err()
+}
+
+#[test]
+fn kinds_and_text() {
+    assert_eq!(captures("(Number) @n", "n"), [["1"], ["0"], ["2"], ["3"], ["4"]]);
+    assert_eq!(captures("\"+\" @op", "op"), [["+"]]);
+    assert_eq!(captures("(Group (Group) @inner)", "inner"), [["23"]]);
+    assert_eq!(captures("(Group _ @any)", "any"), [["1"], ["2"]]);
+}
+
+#[test]
+fn anchors() {
+    // Without anchors, other children may be in between
+    assert_eq!(captures("(Group (Number) @a (Number) @b)", "b"), [["0"], ["3"]]);
+    assert_eq!(captures("(Group (Number) @a . (Number) @b)", "a"), [["2"]]);
+    assert_eq!(captures("(Group . (Number) @first)", "first"), [["1"], ["2"]]);
+    assert_eq!(captures("(Group (Number) @last .)", "last"), [["4"], ["3"]]);
+    assert_eq!(captures("(Group . (Op))", "x").len(), 0);
+}
+
+#[test]
+fn quantifiers() {
+    assert_eq!(captures("(Group (Group)? @g . (Number) @n .)", "g"), [["23"]]);
+    assert_eq!(captures("(Group (Group)? @g (Number) @n .)", "g"), [vec!["23"], vec![]]);
+    assert_eq!(captures("(Group . (Number)* @n (Group))", "n"), [["1"]]);
+    assert_eq!(captures("(Group (Op)* @op . (Ws) @ws)", "op"), [["+"]]);
+    assert_eq!(captures("(Group . (Number)+ @n .)", "n"), [["2", "3"]]);
+    assert_eq!(captures("(Group (Op)+ (Op))", "x").len(), 0);
+}
+
+#[test]
+fn repetitions_are_adjacent() {
+    // The numbers in the outer group aren't next to each other, so each
+    // repetition only matches one of them
+    assert_eq!(captures("(Group (Number)+ @n)", "n"), [vec!["1"], vec!["2", "3"]]);
+    assert_eq!(captures("(Group (Number)+ @n . (Group))", "n"), [["0"]]);
+    assert_eq!(captures("(Group (Number)* @n .)", "n"), [vec!["4"], vec!["2", "3"]]);
+}
+
+#[test]
+fn predicates() {
+    assert_eq!(captures("((Number) @n (#eq? @n \"0\"))", "n"), [["0"]]);
+    assert_eq!(captures("((Number) @n (#not-eq? @n \"0\"))", "n"), [["1"], ["2"], ["3"], ["4"]]);
+    assert_eq!(captures("((Number) @n (#any-of? @n \"1\" \"4\"))", "n"), [["1"], ["4"]]);
+    assert_eq!(captures("((Number) @n (#not-any-of? @n \"1\" \"4\" \"0\"))", "n"), [["2"], ["3"]]);
+    // Predicates among child patterns make the matcher look further
+    assert_eq!(captures("(Group (Number) @n (#eq? @n \"4\"))", "n"), [["4"]]);
+    // Comparing two captures
+    assert_eq!(captures("(Group (Number) @a (Number) @b (#eq? @a @b))", "a").len(), 0);
+    assert_eq!(captures("(Group (Number) @a (Number) @b (#not-eq? @a @b))", "b"), [["0"], ["3"]]);
+    // All nodes of a repeated capture have to pass
+    assert_eq!(captures("(Group (Number)+ @n . (#not-eq? @n \"3\"))", "n"), [["4"]]);
+}
+
+#[test]
+fn multiple_patterns() {
+    let query = Query::new("; numbers\n(Number) @n\n\"+\" @op ; and operators", resolve).unwrap();
+    assert_eq!(query.pattern_count(), 2);
+    assert_eq!(query.capture_names(), ["n", "op"]);
+    let root = tree();
+    let patterns: Vec<_> = query.matches(&root).map(|found| found.pattern).collect();
+    assert_eq!(patterns, [0, 1, 0, 0, 0, 0]);
+}
+
+#[test]
+fn errors() {
+    assert_eq!(error("(Group"), QueryError { offset: 6, kind: QueryErrorKind::UnexpectedEnd });
+    assert_eq!(error("(Group (Nope))"), QueryError { offset: 8, kind: QueryErrorKind::UnknownKind });
+    assert_eq!(error("(Group ]"), QueryError { offset: 7, kind: QueryErrorKind::UnexpectedChar });
+    assert_eq!(error("\"unterminated"), QueryError { offset: 13, kind: QueryErrorKind::UnexpectedEnd });
+    assert_eq!(error("((Number) @n (#nope? @n))"), QueryError { offset: 14, kind: QueryErrorKind::UnknownPredicate });
+    assert_eq!(error("((Number) @n (#eq? @n))"), QueryError { offset: 14, kind: QueryErrorKind::InvalidPredicate });
+    assert_eq!(error("((Number) @n (#eq? @m \"1\"))"), QueryError { offset: 21, kind: QueryErrorKind::UnknownCapture });
+    assert_eq!(error("(Group) Number"), QueryError { offset: 8, kind: QueryErrorKind::UnexpectedChar });
+}
+
+#[test]
+fn escapes() {
+    let root = tree! { SyntaxKind: Group { Op "\"", Ws "\n" } };
+    let query = Query::new("(Group \"\\\"\" @quote \"\\n\" @newline)", resolve).unwrap();
+    let found = query.matches(&root).next().unwrap();
+    assert_eq!(found.get("quote").unwrap().node.to_string(), "\"");
+    assert_eq!(found.get("newline").unwrap().range, rowan2::TextRange::from_to(1.into(), 2.into()));
+}
@@ -0,0 +1,132 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{MutableRoot, Node, Replace, ReplaceError, ReplaceErrorKind};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Add,
+    Group,
+    Number,
+    Op,
+    Ws
+}
+
+fn resolve(name: &str) -> Option<SyntaxKind> {
+    match name {
+        "Add" => Some(SyntaxKind::Add),
+        "Group" => Some(SyntaxKind::Group),
+        "Number" => Some(SyntaxKind::Number),
+        "Op" => Some(SyntaxKind::Op),
+        "Ws" => Some(SyntaxKind::Ws),
+        _ => None
+    }
+}
+
+/// `1+0`, `x+x` and `y + y` with whitespace
+fn tree() -> Node<SyntaxKind, MutableRoot<SyntaxKind>> {
+    tree! { mut SyntaxKind:
+        Group {
+            Add { Number "1", Op "+", Number "0" },
+            Add { Number "x", Op "+", Number "x" },
+            Add {
+                Group { Number "y" },
+                Ws " ",
+                Op "+",
+                Ws " ",
+                Group { Ws " ", Number "y" }
+            }
+        }
+    }
+}
+
+fn error(pattern: &str, template: &str) -> ReplaceError {
+    match Replace::new(pattern, template, resolve) {
+        Ok(_) => panic!("{} -> {} compiled", pattern, template),
+        Err(err) => err
+    }
+}
+
+#[test]
+fn replace_with_captures() {
+    let root = tree();
+    let replace = Replace::new("(Add $a \"+\" Number \"0\")", "$a", resolve).unwrap();
+    let replaced = replace.replace(&root);
+    assert_eq!(replaced.len(), 1);
+    assert_eq!(replaced[0].range, rowan2::TextRange::from_to(0.into(), 3.into()));
+    assert_eq!(replaced[0].node.kind(), SyntaxKind::Number);
+    assert_eq!(root.to_string(), "1x+xy +  y");
+    root.validate().unwrap();
+}
+
+#[test]
+fn templates_build_new_nodes() {
+    let root = tree();
+    let replace = Replace::new("(Add $a _ $b)", "(Group Op \"(\" $b Op \"-\" $a Op \")\")", resolve).unwrap();
+    let replaced = replace.replace(&root);
+    assert_eq!(replaced.len(), 2);
+    assert_eq!(root.to_string(), "(0-1)(x-x)y +  y");
+    assert!(replaced.iter().all(|replacement| replacement.node.kind() == SyntaxKind::Group));
+    root.validate().unwrap();
+}
+
+#[test]
+fn repeated_captures() {
+    let root = tree();
+    // The same capture twice only matches structurally equal nodes
+    let replace = Replace::new("(Add $a \"+\" $a)", "(Add Number \"2\" Op \"*\" $a)", resolve).unwrap();
+    let found = replace.matches(&root);
+    assert_eq!(found.len(), 1);
+    assert_eq!(found[0].node.to_string(), "x+x");
+    assert_eq!(found[0].captures.len(), 1);
+    assert_eq!(found[0].captures[0].0, "a");
+
+    replace.replace(&root);
+    assert_eq!(root.to_string(), "1+02*xy +  y");
+    root.validate().unwrap();
+}
+
+#[test]
+fn trivia() {
+    let root = tree();
+    let replace = Replace::new("(Add $a \"+\" $a)", "$a", resolve).unwrap();
+    assert_eq!(replace.matches(&root).len(), 1);
+
+    // Skipping whitespace lets the last addition match too, since its groups
+    // are only different in trivia
+    let replace = replace.trivia(|kind| kind == SyntaxKind::Ws);
+    let found = replace.matches(&root);
+    assert_eq!(found.len(), 2);
+    assert_eq!(found[1].captures[0].1.to_string(), "y");
+
+    replace.replace(&root);
+    // Trivia inside the captured node is kept, the rest is lost
+    assert_eq!(root.to_string(), "1+0xy");
+    root.validate().unwrap();
+}
+
+#[test]
+fn dry_run() {
+    let root = tree();
+    let replace = Replace::new("(Add _ \"+\" _)", "Number \"0\"", resolve).unwrap();
+    assert!(!replace.is_match(&root));
+    assert!(replace.is_match(&root.first_child().unwrap()));
+    let ranges: Vec<_> = replace.matches(&root).into_iter().map(|found| found.range).collect();
+    assert_eq!(ranges.len(), 2);
+    assert_eq!(root.to_string(), "1+0x+xy +  y");
+}
+
+#[test]
+fn errors() {
+    let pattern = |offset, kind| ReplaceError { in_template: false, offset, kind };
+    let template = |offset, kind| ReplaceError { in_template: true, offset, kind };
+
+    assert_eq!(error("(Add $a", "$a"), pattern(7, ReplaceErrorKind::UnexpectedEnd));
+    assert_eq!(error("(Add \"unterminated)", "_"), pattern(19, ReplaceErrorKind::UnexpectedEnd));
+    assert_eq!(error("(Nope $a)", "$a"), pattern(1, ReplaceErrorKind::UnknownKind));
+    assert_eq!(error("(Add $a) $b", "$a"), pattern(9, ReplaceErrorKind::UnexpectedChar));
+    assert_eq!(error("Number $a", "$a"), pattern(7, ReplaceErrorKind::UnexpectedChar));
+    assert_eq!(error("(Add $a)", "(Group $b)"), template(7, ReplaceErrorKind::UnknownCapture));
+    assert_eq!(error("(Add $a)", "(Group _)"), template(7, ReplaceErrorKind::NotBuildable));
+    assert_eq!(error("(Add $a)", "(Group \"+\")"), template(7, ReplaceErrorKind::NotBuildable));
+}
@@ -0,0 +1,172 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{MutableRoot, Node, Rewriter};
+use std::cell::Cell;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Add,
+    Group,
+    Number,
+    Op
+}
+
+type MutNode = Node<SyntaxKind, MutableRoot<SyntaxKind>>;
+
+/// `Add { Number, Op, Number }`, which can be folded into a number
+fn is_constant(node: &MutNode) -> bool {
+    node.kind() == SyntaxKind::Add
+        && node.children().all(|child| child.kind() != SyntaxKind::Add)
+}
+fn fold(node: MutNode) -> Option<MutNode> {
+    let sum: i32 = node.children()
+        .filter(|child| child.kind() == SyntaxKind::Number)
+        .map(|child| child.to_string().parse::<i32>().unwrap())
+        .sum();
+    let number = node.insert_before(SyntaxKind::Number, Some(sum.to_string().into()));
+    node.remove();
+    Some(number)
+}
+
+#[test]
+fn fixpoint_over_several_passes() {
+    let root = tree! { mut SyntaxKind:
+        Group {
+            Add {
+                Add { Number "1", Op "+", Number "2" },
+                Op "+",
+                Add {
+                    Add { Number "3", Op "+", Number "4" },
+                    Op "+",
+                    Number "5"
+                }
+            }
+        }
+    };
+    let report = Rewriter::new()
+        .rule("fold", is_constant, fold)
+        .run(&root)
+        .unwrap();
+
+    assert_eq!(root.to_string(), "15");
+    assert_eq!(report.root, Some(root.clone()));
+    assert_eq!(report.passes, 4);
+    let passes: Vec<_> = report.fired.iter().map(|firing| firing.pass).collect();
+    assert_eq!(passes, [0, 0, 1, 2]);
+    assert!(report.fired.iter().all(|firing| firing.rule == "fold"));
+    assert_eq!(report.fired[0].range, rowan2::TextRange::from_to(0.into(), 3.into()));
+    root.validate().unwrap();
+}
+
+#[test]
+fn replace_root() {
+    let root = tree! { mut SyntaxKind:
+        Group {
+            Add {
+                Add { Number "1", Op "+", Number "2" },
+                Op "+",
+                Number "3"
+            }
+        }
+    };
+    let add = root.first_child().unwrap();
+    let report = Rewriter::new()
+        .rule("fold", is_constant, fold)
+        .run(&add)
+        .unwrap();
+
+    assert!(!add.is_alive());
+    let new = report.root.unwrap();
+    assert_eq!(new.kind(), SyntaxKind::Number);
+    assert_eq!(new.to_string(), "6");
+    assert_eq!(new.parent(), Some(root.clone()));
+    assert_eq!(report.passes, 3);
+    root.validate().unwrap();
+
+    // Removing the root without replacement stops right away
+    let report = Rewriter::new()
+        .rule("remove", |node| node.kind() == SyntaxKind::Number, |node| {
+            node.remove();
+            None
+        })
+        .run(&new)
+        .unwrap();
+    assert_eq!(report.root, None);
+    assert_eq!(report.passes, 1);
+    assert_eq!(report.fired.len(), 1);
+    assert_eq!(root.to_string(), "");
+}
+
+#[test]
+fn never_settles() {
+    let root = tree! { mut SyntaxKind:
+        Group { Number "1" }
+    };
+    let err = Rewriter::new()
+        .rule("grow", |node| node.kind() == SyntaxKind::Group, |node| {
+            node.append_child(SyntaxKind::Number, Some("1".into()));
+            Some(node)
+        })
+        .max_passes(5)
+        .run(&root)
+        .unwrap_err();
+
+    assert_eq!(err.report.passes, 5);
+    assert_eq!(err.report.fired.len(), 5);
+    assert_eq!(err.report.fired[4].pass, 4);
+    assert_eq!(err.report.root, Some(root.clone()));
+    assert_eq!(err.to_string(), "rules still fired after 5 passes");
+    assert_eq!(root.to_string(), "111111");
+}
+
+#[test]
+fn removed_nodes_are_skipped() {
+    let root = tree! { mut SyntaxKind:
+        Group {
+            Number "1",
+            Number "1",
+            Number "1",
+            Group { Number "2" },
+            Number "2"
+        }
+    };
+    let visited = Cell::new(0);
+    let report = Rewriter::new()
+        // Drop the next number if it's a duplicate, which would panic when
+        // looking at a removed node
+        .rule(
+            "dedupe",
+            |node| {
+                visited.set(visited.get() + 1);
+                node.kind() == SyntaxKind::Number
+                    && node.next_sibling().is_some_and(|next| next.to_string() == node.to_string())
+            },
+            |node| {
+                node.next_sibling().unwrap().remove();
+                Some(node)
+            }
+        )
+        // Removing a branch skips its children too
+        .rule(
+            "drop_group",
+            |node| node.kind() == SyntaxKind::Group && node.parent().is_some(),
+            |node| {
+                node.remove();
+                None
+            }
+        )
+        .run(&root)
+        .unwrap();
+
+    assert_eq!(root.to_string(), "12");
+    let fired: Vec<_> = report.fired.iter()
+        .map(|firing| (&*firing.rule, firing.pass))
+        .collect();
+    assert_eq!(fired, [("dedupe", 0), ("drop_group", 0), ("dedupe", 1)]);
+    assert_eq!(report.passes, 3);
+    // Each pass skips the number removed by "dedupe", and the first one also
+    // skips the number in the removed group
+    assert_eq!(visited.get(), (7 - 2) + (4 - 1) + 3);
+    root.validate().unwrap();
+}
@@ -0,0 +1,218 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{Node, OwnedRoot, Structural};
+use std::collections::HashSet;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation,
+    Trivia
+}
+
+type OwnedNode = Node<SyntaxKind, OwnedRoot<SyntaxKind>>;
+
+fn tree() -> OwnedNode {
+    tree! { SyntaxKind:
+        Group {
+            Group { Number "1", Operation "+", Number "2" },
+            Operation "*",
+            Group { Number "1", Operation "+", Number "2" }
+        }
+    }
+}
+
+#[test]
+// The cached fingerprints are the only interior mutability, and they don't
+// change the hash
+#[allow(clippy::mutable_key_type)]
+fn equal_subtrees_dedupe() {
+    let first = tree();
+    let second = tree! { SyntaxKind:
+        Group {
+            Number "3",
+            Group { Number "1", Operation "+", Number "2" }
+        }
+    };
+    let left = first.first_child().unwrap();
+    let right = first.last_child().unwrap();
+    let other = second.last_child().unwrap();
+
+    assert_ne!(left, right);
+    assert!(left.structurally_eq(&right));
+    assert!(left.structurally_eq(&other));
+    assert_eq!(left.fingerprint(), right.fingerprint());
+    assert_eq!(left.fingerprint(), other.fingerprint());
+    assert_ne!(first.fingerprint(), second.fingerprint());
+
+    let mut set = HashSet::new();
+    for node in first.descendants().chain(second.descendants()) {
+        set.insert(Structural(node));
+    }
+    // Both roots, `1+2`, `1`, `+`, `2`, `*` and `3`
+    assert_eq!(set.len(), 8);
+    assert!(set.contains(&Structural(other.clone())));
+    assert!(set.contains(&Structural(tree! { SyntaxKind: Number "2" })));
+    assert!(!set.contains(&Structural(tree! { SyntaxKind: Number "4" })));
+
+    // Borrowed and mutable nodes work the same way
+    let thawed = second.thaw();
+    assert_eq!(thawed.last_child().unwrap().fingerprint(), left.fingerprint());
+    assert_eq!(Structural(left.borrowed()), Structural(other.borrowed()));
+}
+
+#[test]
+fn differences_change_fingerprint() {
+    let original = tree().first_child().unwrap();
+    let changed = [
+        // A different leaf kind
+        tree! { SyntaxKind: Group { Number "1", Number "+", Number "2" } },
+        // A different branch kind
+        tree! { SyntaxKind: Operation { Number "1", Operation "+", Number "2" } },
+        // Different leaf text
+        tree! { SyntaxKind: Group { Number "1", Operation "-", Number "2" } },
+        // The same text split up differently
+        tree! { SyntaxKind: Group { Number "1+", Number "2" } },
+        tree! { SyntaxKind: Group { Group { Number "1", Operation "+" }, Number "2" } },
+        // A leaf instead of an empty branch
+        tree! { SyntaxKind: Group { Number "1", Operation "+", Number "2", Group {} } },
+        tree! { SyntaxKind: Group { Number "1", Operation "+", Number "2", Group "" } }
+    ];
+    for node in &changed {
+        assert!(!node.structurally_eq(&original), "{}", node);
+        assert_ne!(node.fingerprint(), original.fingerprint(), "{}", node);
+    }
+    assert_ne!(changed[5].fingerprint(), changed[6].fingerprint());
+}
+
+#[test]
+fn edits_invalidate_fingerprints() {
+    let frozen = tree();
+    let before = frozen.fingerprint();
+    let left = frozen.first_child().unwrap().fingerprint();
+
+    let thawed = frozen.thaw();
+    assert_eq!(thawed.fingerprint(), before);
+
+    // Change the leaf text of the last group, which doesn't affect the first
+    let last = thawed.last_child().unwrap();
+    last.last_child().unwrap().insert_after(SyntaxKind::Number, Some("3".into()));
+    assert_ne!(thawed.fingerprint(), before);
+    assert_ne!(last.fingerprint(), left);
+    assert_eq!(thawed.first_child().unwrap().fingerprint(), left);
+
+    // Changing it back gives the old fingerprint again
+    last.last_child().unwrap().remove();
+    assert_eq!(thawed.fingerprint(), before);
+    assert_eq!(last.fingerprint(), left);
+
+    // The frozen tree never changed
+    assert_eq!(frozen.fingerprint(), before);
+    assert_eq!(frozen.last_child().unwrap().fingerprint(), left);
+    assert_eq!(thawed.freeze().fingerprint(), before);
+}
+
+fn is_trivia(kind: SyntaxKind) -> bool {
+    kind == SyntaxKind::Trivia
+}
+
+#[test]
+fn trivia_is_ignored() {
+    let plain = tree! { SyntaxKind:
+        Group {
+            Group { Number "1", Operation "+", Number "2" },
+            Operation "*",
+            Number "3"
+        }
+    };
+    let spaced = tree! { SyntaxKind:
+        Group {
+            Trivia " ",
+            Group { Trivia "", Number "1", Trivia " ", Operation "+", Number "2", Trivia { Trivia " " } },
+            Trivia { Number "ignored" },
+            Operation "*",
+            Number "3",
+            Trivia "\n"
+        }
+    };
+    assert!(!plain.structurally_eq(&spaced));
+    assert!(plain.equivalent(&spaced, is_trivia).is_ok());
+    assert!(spaced.equivalent(&plain, is_trivia).is_ok());
+    assert!(spaced.equivalent(&spaced.thaw(), is_trivia).is_ok());
+    assert!(plain.first_child().unwrap().equivalent(&spaced.children().nth(1).unwrap(), is_trivia).is_ok());
+
+    // Trivia only at the start and end, on both sides
+    let start = tree! { SyntaxKind: Group { Trivia " ", Number "1", Trivia " " } };
+    let end = tree! { SyntaxKind: Group { Number "1", Trivia "", Trivia { Trivia " " } } };
+    assert!(start.equivalent(&end, is_trivia).is_ok());
+    assert!(end.equivalent(&start, is_trivia).is_ok());
+}
+
+#[test]
+fn first_divergence() {
+    let left = tree! { SyntaxKind:
+        Group {
+            Trivia " ",
+            Group { Number "1", Operation "+", Number "2" },
+            Operation "*",
+            Number "3"
+        }
+    };
+    let divergence = |right: &OwnedNode| {
+        let divergence = left.equivalent(right, is_trivia).unwrap_err();
+        (
+            divergence.left.map(|node| format!("{:?} {}", node.kind(), node)),
+            divergence.right.map(|node| format!("{:?} {}", node.kind(), node))
+        )
+    };
+    let some = |text: &str| Some(text.to_string());
+
+    // A kind mismatch, after trivia that only the right side has
+    let right = tree! { SyntaxKind:
+        Group {
+            Group { Number "1", Trivia " ", Number "+", Number "2" },
+            Operation "*",
+            Number "4"
+        }
+    };
+    assert_eq!(divergence(&right), (some("Operation +"), some("Number +")));
+
+    // A text mismatch, which isn't reported again for the parent
+    let right = tree! { SyntaxKind:
+        Group {
+            Group { Number "1", Operation "+", Number "2" },
+            Operation "*",
+            Number "4",
+            Trivia " "
+        }
+    };
+    assert_eq!(divergence(&right), (some("Number 3"), some("Number 4")));
+
+    // A branch where the other side has a leaf
+    let right = tree! { SyntaxKind:
+        Group { Number "1+2", Operation "*", Number "3" }
+    };
+    assert_eq!(divergence(&right), (some("Group 1+2"), some("Number 1+2")));
+
+    // One side leaves a branch while the other one has another node, and the
+    // text of the branch includes its trivia
+    let right = tree! { SyntaxKind:
+        Group {
+            Group { Number "1", Operation "+", Number "2" },
+            Operation "*",
+            Number "3",
+            Number "4"
+        }
+    };
+    assert_eq!(divergence(&right), (some("Group  1+2*3"), some("Number 4")));
+    let right = tree! { SyntaxKind:
+        Group {
+            Group { Number "1", Operation "+" },
+            Operation "*",
+            Number "3"
+        }
+    };
+    assert_eq!(divergence(&right), (some("Number 2"), some("Group 1+")));
+}
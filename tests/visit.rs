@@ -0,0 +1,165 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{KindDispatch, Node, TreeRoot, VisitControl, Visitor};
+use std::cell::RefCell;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation
+}
+
+/// `1+2*3-4` with `2*3` in its own group, either immutable or mutable
+macro_rules! example {
+    ($($mut:ident)?) => {
+        tree! { $($mut)? SyntaxKind:
+            Group {
+                Number "1",
+                Operation "+",
+                Group {
+                    Number "2",
+                    Operation "*",
+                    Number "3"
+                },
+                Operation "-",
+                Number "4"
+            }
+        }
+    };
+}
+
+/// Records all hooks, returning `control` for the hook call described by
+/// `at` and `Continue` otherwise
+struct Recorder {
+    calls: Vec<String>,
+    at: &'static str,
+    control: VisitControl
+}
+impl Recorder {
+    fn new(at: &'static str, control: VisitControl) -> Self {
+        Self { calls: Vec::new(), at, control }
+    }
+    fn record(&mut self, call: String) -> VisitControl {
+        let control = if call == self.at { self.control } else { VisitControl::Continue };
+        self.calls.push(call);
+        control
+    }
+}
+impl<R: TreeRoot<SyntaxKind>> Visitor<SyntaxKind, R> for Recorder {
+    fn enter(&mut self, node: &Node<SyntaxKind, R>) -> VisitControl {
+        self.record(format!("enter {}", node))
+    }
+    fn leave(&mut self, node: &Node<SyntaxKind, R>) -> VisitControl {
+        self.record(format!("leave {}", node))
+    }
+    fn visit_leaf(&mut self, node: &Node<SyntaxKind, R>) -> VisitControl {
+        self.record(format!("leaf {}", node))
+    }
+}
+
+fn check_controls<R: TreeRoot<SyntaxKind>>(root: &Node<SyntaxKind, R>) {
+    let visit = |at, control| {
+        let mut recorder = Recorder::new(at, control);
+        let finished = root.accept(&mut recorder);
+        (finished, recorder.calls)
+    };
+
+    let (finished, calls) = visit("", VisitControl::Continue);
+    assert!(finished);
+    assert_eq!(calls, [
+        "enter 1+2*3-4",
+        "leaf 1", "leaf +",
+        "enter 2*3", "leaf 2", "leaf *", "leaf 3", "leave 2*3",
+        "leaf -", "leaf 4",
+        "leave 1+2*3-4"
+    ]);
+
+    // Only the children of the inner group are skipped, it's still left
+    let (finished, calls) = visit("enter 2*3", VisitControl::SkipChildren);
+    assert!(finished);
+    assert_eq!(calls, [
+        "enter 1+2*3-4",
+        "leaf 1", "leaf +",
+        "enter 2*3", "leave 2*3",
+        "leaf -", "leaf 4",
+        "leave 1+2*3-4"
+    ]);
+    // Anywhere else it does nothing
+    assert_eq!(visit("leaf +", VisitControl::SkipChildren), visit("", VisitControl::Continue));
+    assert_eq!(visit("leave 2*3", VisitControl::SkipChildren), visit("", VisitControl::Continue));
+
+    // Stopping ends the walk right away, from any hook
+    let (finished, calls) = visit("leaf *", VisitControl::Stop);
+    assert!(!finished);
+    assert_eq!(calls, ["enter 1+2*3-4", "leaf 1", "leaf +", "enter 2*3", "leaf 2", "leaf *"]);
+    let (finished, calls) = visit("enter 2*3", VisitControl::Stop);
+    assert!(!finished);
+    assert_eq!(calls.last().unwrap(), "enter 2*3");
+    let (finished, calls) = visit("leave 2*3", VisitControl::Stop);
+    assert!(!finished);
+    assert_eq!(calls.len(), 8);
+    let (finished, calls) = visit("leave 1+2*3-4", VisitControl::Stop);
+    assert!(!finished);
+    assert_eq!(calls.len(), 11);
+
+    // Visiting a subtree stays inside of it
+    let mut recorder = Recorder::new("", VisitControl::Continue);
+    assert!(root.children().nth(2).unwrap().accept(&mut recorder));
+    assert_eq!(recorder.calls, ["enter 2*3", "leaf 2", "leaf *", "leaf 3", "leave 2*3"]);
+}
+
+fn check_dispatch<R: TreeRoot<SyntaxKind>>(root: &Node<SyntaxKind, R>) {
+    let calls = RefCell::new(Vec::new());
+    let record = |name: &'static str| {
+        let calls = &calls;
+        move |node: &Node<SyntaxKind, R>| {
+            calls.borrow_mut().push(format!("{} {}", name, node));
+            VisitControl::Continue
+        }
+    };
+    let mut dispatch = KindDispatch::new()
+        .on(SyntaxKind::Group, record("enter"))
+        .on_leave(SyntaxKind::Group, record("leave"))
+        .on(SyntaxKind::Number, record("number"))
+        // Leaves are never left, so this is never called
+        .on_leave(SyntaxKind::Number, record("leave number"));
+    assert!(root.accept(&mut dispatch));
+    assert_eq!(*calls.borrow(), [
+        "enter 1+2*3-4",
+        "number 1",
+        "enter 2*3", "number 2", "number 3", "leave 2*3",
+        "number 4",
+        "leave 1+2*3-4"
+    ]);
+
+    // Handlers can control the walk too
+    calls.borrow_mut().clear();
+    let mut dispatch = KindDispatch::new()
+        .on(SyntaxKind::Group, |node: &Node<SyntaxKind, R>| if node.parent().is_some() {
+            VisitControl::SkipChildren
+        } else {
+            VisitControl::Continue
+        })
+        .on(SyntaxKind::Number, record("number"))
+        .on(SyntaxKind::Operation, |node: &Node<SyntaxKind, R>| if node.to_string() == "-" {
+            VisitControl::Stop
+        } else {
+            VisitControl::Continue
+        });
+    assert!(!root.accept(&mut dispatch));
+    assert_eq!(*calls.borrow(), ["number 1"]);
+}
+
+#[test]
+fn controls() {
+    check_controls(&example!());
+    check_controls(&example!(mut));
+}
+
+#[test]
+fn kind_dispatch() {
+    check_dispatch(&example!());
+    check_dispatch(&example!(mut));
+}
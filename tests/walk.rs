@@ -0,0 +1,292 @@
+#[macro_use]
+extern crate rowan2;
+
+use rowan2::{Node, NodeWalker, OwnedRoot, TextRange, TreeBuilder, TreeRoot, WalkEvent};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum SyntaxKind {
+    Group,
+    Number,
+    Operation
+}
+
+type OwnedNode = Node<SyntaxKind, OwnedRoot<SyntaxKind>>;
+
+fn tree() -> OwnedNode {
+    tree! { SyntaxKind:
+        Group {
+            Number "1",
+            Operation "+",
+            Group {
+                Number "2",
+                Operation "*",
+                Number "3"
+            }
+        }
+    }
+}
+
+fn range(start: u32, end: u32) -> TextRange {
+    TextRange::from_to(start.into(), end.into())
+}
+
+/// Walk and describe each event as its depth, the direction and the text of
+/// the node. `skip` is called for each event and the subtree is skipped if it
+/// returns true.
+fn events<R, F>(mut walker: NodeWalker<SyntaxKind, R>, skip: F) -> Vec<String>
+    where R: TreeRoot<SyntaxKind>,
+          F: Fn(&WalkEvent<SyntaxKind, R>) -> bool
+{
+    let mut events = Vec::new();
+    while let Some((depth, event)) = walker.next() {
+        events.push(match event {
+            WalkEvent::Enter(ref node) => format!("{}>{}", depth, node),
+            WalkEvent::Leave(ref node) => format!("{}<{}", depth, node)
+        });
+        if skip(&event) {
+            walker.skip_subtree();
+        }
+    }
+    events
+}
+fn walk<R: TreeRoot<SyntaxKind>>(walker: NodeWalker<SyntaxKind, R>) -> Vec<String> {
+    events(walker, |_| false)
+}
+fn is_enter<R: TreeRoot<SyntaxKind>>(event: &WalkEvent<SyntaxKind, R>, text: &str) -> bool {
+    match event {
+        WalkEvent::Enter(node) => node.to_string() == text,
+        WalkEvent::Leave(_) => false
+    }
+}
+
+#[test]
+fn order_and_depth() {
+    let root = tree();
+    assert_eq!(walk(root.walk()), [
+        "0>1+2*3",
+        "1>1", "1<1",
+        "1>+", "1<+",
+        "1>2*3",
+        "2>2", "2<2",
+        "2>*", "2<*",
+        "2>3", "2<3",
+        "1<2*3",
+        "0<1+2*3"
+    ]);
+    assert_eq!(walk(root.walk().reversed()), [
+        "0>1+2*3",
+        "1>2*3",
+        "2>3", "2<3",
+        "2>*", "2<*",
+        "2>2", "2<2",
+        "1<2*3",
+        "1>+", "1<+",
+        "1>1", "1<1",
+        "0<1+2*3"
+    ]);
+
+    // Walking a child starts at depth 0 and doesn't leave it
+    let inner = root.last_child().unwrap();
+    assert_eq!(walk(inner.walk()), ["0>2*3", "1>2", "1<2", "1>*", "1<*", "1>3", "1<3", "0<2*3"]);
+    assert_eq!(walk(inner.walk().reversed()), ["0>2*3", "1>3", "1<3", "1>*", "1<*", "1>2", "1<2", "0<2*3"]);
+    let leaf = root.first_child().unwrap();
+    assert_eq!(walk(leaf.walk()), ["0>1", "0<1"]);
+    assert_eq!(walk(leaf.walk().reversed()), ["0>1", "0<1"]);
+}
+
+#[test]
+fn max_depth() {
+    let root = tree();
+    assert_eq!(walk(root.walk().max_depth(0)), ["0>1+2*3", "0<1+2*3"]);
+    assert_eq!(walk(root.walk().max_depth(1)), [
+        "0>1+2*3",
+        "1>1", "1<1",
+        "1>+", "1<+",
+        "1>2*3", "1<2*3",
+        "0<1+2*3"
+    ]);
+    assert_eq!(walk(root.walk().max_depth(1).reversed()), [
+        "0>1+2*3",
+        "1>2*3", "1<2*3",
+        "1>+", "1<+",
+        "1>1", "1<1",
+        "0<1+2*3"
+    ]);
+    assert_eq!(walk(root.walk().max_depth(2)), walk(root.walk()));
+}
+
+#[test]
+fn in_range() {
+    let root = tree();
+
+    // An empty range touches both leaves around it
+    assert_eq!(walk(root.walk().in_range(range(1, 1))), [
+        "0>1+2*3", "1>1", "1<1", "1>+", "1<+", "0<1+2*3"
+    ]);
+    assert_eq!(walk(root.walk().in_range(range(1, 1)).reversed()), [
+        "0>1+2*3", "1>+", "1<+", "1>1", "1<1", "0<1+2*3"
+    ]);
+
+    // `+` and `*` only touch the range at its start and end
+    assert_eq!(walk(root.walk().in_range(range(2, 3))), [
+        "0>1+2*3",
+        "1>+", "1<+",
+        "1>2*3",
+        "2>2", "2<2",
+        "2>*", "2<*",
+        "1<2*3",
+        "0<1+2*3"
+    ]);
+    assert_eq!(walk(root.walk().in_range(range(2, 3)).reversed()), [
+        "0>1+2*3",
+        "1>2*3",
+        "2>*", "2<*",
+        "2>2", "2<2",
+        "1<2*3",
+        "1>+", "1<+",
+        "0<1+2*3"
+    ]);
+
+    // At the end of the tree
+    assert_eq!(walk(root.walk().in_range(range(5, 5))), [
+        "0>1+2*3", "1>2*3", "2>3", "2<3", "1<2*3", "0<1+2*3"
+    ]);
+    assert_eq!(walk(root.walk().in_range(range(5, 5)).reversed()), [
+        "0>1+2*3", "1>2*3", "2>3", "2<3", "1<2*3", "0<1+2*3"
+    ]);
+
+    // The start node is always walked, even if it's out of range
+    let inner = root.last_child().unwrap();
+    assert_eq!(walk(inner.walk().in_range(range(0, 1))), ["0>2*3", "0<2*3"]);
+    assert_eq!(walk(inner.walk().in_range(range(0, 1)).reversed()), ["0>2*3", "0<2*3"]);
+
+    // Combined with a maximum depth
+    assert_eq!(walk(root.walk().in_range(range(2, 3)).max_depth(1)), [
+        "0>1+2*3", "1>+", "1<+", "1>2*3", "1<2*3", "0<1+2*3"
+    ]);
+}
+
+#[test]
+fn skip_subtree() {
+    let root = tree();
+    let skip_inner = |event: &_| is_enter(event, "2*3");
+    assert_eq!(events(root.walk(), skip_inner), [
+        "0>1+2*3", "1>1", "1<1", "1>+", "1<+", "1>2*3", "1<2*3", "0<1+2*3"
+    ]);
+    assert_eq!(events(root.walk().reversed(), skip_inner), [
+        "0>1+2*3", "1>2*3", "1<2*3", "1>+", "1<+", "1>1", "1<1", "0<1+2*3"
+    ]);
+    // Skipping the start node only leaves it
+    assert_eq!(events(root.walk(), |event| is_enter(event, "1+2*3")), ["0>1+2*3", "0<1+2*3"]);
+
+    // Skipping a leaf or after leaving a node changes nothing
+    assert_eq!(events(root.walk(), |event| is_enter(event, "+")), walk(root.walk()));
+    assert_eq!(events(root.walk().reversed(), |event| is_enter(event, "3")), walk(root.walk().reversed()));
+    assert_eq!(events(root.walk(), |event| matches!(event, WalkEvent::Leave(_))), walk(root.walk()));
+}
+
+/// What walking in a range should do, calculated recursively using
+/// `Node::range`, not entering the children of the node with the text `skip`
+fn expected<R: TreeRoot<SyntaxKind>>(
+    node: &Node<SyntaxKind, R>,
+    range: TextRange,
+    reverse: bool,
+    skip: &str,
+    depth: usize,
+    events: &mut Vec<String>
+) {
+    events.push(format!("{}>{}", depth, node));
+    if node.to_string() != skip {
+        let children: Vec<_> = if reverse {
+            node.children_rev().collect()
+        } else {
+            node.children().collect()
+        };
+        for child in children {
+            if child.range().intersection(&range).is_some() {
+                expected(&child, range, reverse, skip, depth + 1, events);
+            }
+        }
+    }
+    events.push(format!("{}<{}", depth, node));
+}
+
+#[test]
+fn in_range_on_edited_tree() {
+    let owned = tree! { SyntaxKind:
+        Group {
+            Number "12",
+            Group { Number "3", Group { Operation "+" } },
+            Group {},
+            Number "4",
+            Group { Number "56", Operation "*" }
+        }
+    };
+    let root = owned.thaw();
+    // Change lengths so the stored ranges of the owned tree are wrong
+    root.first_child().unwrap().insert_after(SyntaxKind::Number, Some("789".into()));
+    root.children().nth(2).unwrap().first_child().unwrap().remove();
+    assert_eq!(root.to_string(), "12789+456*");
+
+    let check = |node: &Node<SyntaxKind, _>, range, skip: &str| {
+        for &reverse in &[false, true] {
+            let mut walker = node.walk().in_range(range);
+            if reverse {
+                walker = walker.reversed();
+            }
+            let mut events_expected = Vec::new();
+            expected(node, range, reverse, skip, 0, &mut events_expected);
+            let events = events(walker, |event| is_enter(event, skip));
+            assert_eq!(events, events_expected, "{:?} reverse: {}", range, reverse);
+        }
+    };
+    let inner = root.children().nth(2).unwrap();
+    for start in 0..=11 {
+        for end in start..=11 {
+            let range = range(start, end);
+            for skip in &["", "+", "789", "56*", "12789+456*"] {
+                check(&root, range, skip);
+            }
+            check(&inner, range, "");
+            check(&root.last_child().unwrap(), range, "");
+        }
+    }
+    assert_eq!(walk(root.walk().in_range(range(6, 6))), [
+        "0>12789+456*", "1>+", "2>+", "3>+", "3<+", "2<+", "1<+", "1>", "1<", "1>4", "1<4", "0<12789+456*"
+    ]);
+    assert_eq!(events(root.walk().in_range(range(5, 7)).reversed(), |event| is_enter(event, "+")), [
+        "0>12789+456*",
+        "1>56*", "2>56", "2<56", "1<56*",
+        "1>4", "1<4",
+        "1>", "1<",
+        "1>+", "1<+",
+        "1>789", "1<789",
+        "0<12789+456*"
+    ]);
+}
+
+#[test]
+fn in_range_on_wide_tree() {
+    const WIDTH: u32 = 100_000;
+
+    let mut builder = TreeBuilder::new();
+    builder.start_internal(SyntaxKind::Group);
+    for i in 0..WIDTH {
+        builder.leaf(SyntaxKind::Number, if i % 2 == 0 { "a" } else { "b" }.into());
+    }
+    builder.finish_internal();
+    // Mutable trees don't store ranges
+    let root = builder.finish_mut();
+    assert_eq!(root.walk().in_range(range(0, WIDTH)).count() as u32, 2 * WIDTH + 2);
+
+    // Both leaves touching the range are walked, in either direction
+    let leaves = |walker: NodeWalker<_, _>| -> Vec<String> {
+        walker.skip(1).step_by(2).take_while(|(depth, _)| *depth == 1)
+            .map(|(_, event)| match event {
+                WalkEvent::Enter(node) | WalkEvent::Leave(node) => node.to_string()
+            })
+            .collect()
+    };
+    assert_eq!(leaves(root.walk().in_range(range(WIDTH - 1, WIDTH))), ["a", "b"]);
+    assert_eq!(leaves(root.walk().in_range(range(WIDTH / 2, WIDTH / 2 + 1)).reversed()), ["b", "a", "b"]);
+}